@@ -0,0 +1,103 @@
+use std::fmt::Display;
+
+use crate::tokenizer::{keyword_token, tokenize, Spanned, Token};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "Error"),
+            Severity::Warning => write!(f, "Warning"),
+        }
+    }
+}
+
+// A column of 0 means only the line is known, as for findings made on the
+// syntax tree.
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.column == 0 {
+            return write!(
+                f,
+                "[line {}] {}: {}",
+                self.line, self.severity, self.message
+            );
+        }
+        write!(
+            f,
+            "[line {}, col {}] {}: {}",
+            self.line, self.column, self.severity, self.message
+        )
+    }
+}
+
+// Every problem in the source at once, ordered by position. Only lexical
+// errors are collected for now.
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = tokenize(source)
+        .into_iter()
+        .filter_map(|spanned| match spanned.token {
+            Token::Invalid(e) => Some(Diagnostic {
+                line: e.line,
+                column: e.column,
+                severity: Severity::Error,
+                message: e.message,
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+// Opt-in lint: identifiers such as `Var` or `IF` that only differ from a
+// keyword by case are legal, but are usually a typo for the keyword.
+pub fn lint_identifiers(source: &str, tokens: &[Spanned<Token>]) -> Vec<Diagnostic> {
+    tokens
+        .iter()
+        .filter_map(|spanned| {
+            let Token::Identifier(name) = &spanned.token else {
+                return None;
+            };
+            let keyword = name.to_lowercase();
+            if *keyword == **name || keyword_token(&keyword).is_none() {
+                return None;
+            }
+            Some(Diagnostic {
+                line: spanned.line,
+                column: column(source, spanned.span.start),
+                severity: Severity::Warning,
+                message: format!(
+                    "'{}' differs from the keyword '{}' only by case.",
+                    name, keyword
+                ),
+            })
+        })
+        .collect()
+}
+
+// 1-based, counted in characters like the tokenizer's error columns.
+fn column(source: &str, offset: usize) -> usize {
+    source[..offset]
+        .chars()
+        .rev()
+        .take_while(|&c| c != '\n' && c != '\r')
+        .count()
+        + 1
+}
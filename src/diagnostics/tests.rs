@@ -0,0 +1,43 @@
+use super::*;
+
+fn lints(source: &str) -> Vec<Diagnostic> {
+    lint_identifiers(source, &tokenize(source))
+}
+
+#[test]
+fn only_case_variants_of_keywords_are_linted() {
+    let warnings = lints("var VAR = 1;\nvar variable = VAR;");
+    assert_eq!(
+        warnings
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>(),
+        [
+            "[line 1, col 5] Warning: 'VAR' differs from the keyword 'var' only by case.",
+            "[line 2, col 16] Warning: 'VAR' differs from the keyword 'var' only by case."
+        ]
+    );
+    assert!(lints("var var_ = 1;").is_empty());
+}
+
+#[test]
+fn lint_columns_restart_after_a_bare_carriage_return() {
+    let lints = lints("var a;\r  VAR b;");
+    assert_eq!((lints[0].line, lints[0].column), (2, 3));
+}
+
+#[test]
+fn diagnostics_collects_every_lexical_error_in_order() {
+    let diagnostics = diagnostics("var a = @;\n\nprint ~1;\nvar b = \"open")
+        .iter()
+        .map(Diagnostic::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        [
+            "[line 1, col 9] Error: Unexpected character: @",
+            "[line 3, col 7] Error: Unexpected character: ~",
+            "[line 4, col 9] Error: Unterminated string."
+        ]
+    );
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use super::{Function, Value};
+
+#[derive(Clone, Debug)]
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub methods: HashMap<String, Function>,
+    pub static_methods: HashMap<String, Function>,
+}
+
+impl Class {
+    pub fn find_method(&self, name: &str) -> Option<&Function> {
+        self.methods.get(name).or_else(|| {
+            self.superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find_method(name))
+        })
+    }
+
+    pub fn find_static_method(&self, name: &str) -> Option<&Function> {
+        self.static_methods.get(name).or_else(|| {
+            self.superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find_static_method(name))
+        })
+    }
+
+    pub fn arity(&self) -> usize {
+        self.find_method("init").map_or(0, Function::arity)
+    }
+}
+
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: HashMap<String, Value>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Self {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+}
+
+// Fields may point back at this instance, so only the class name is shown.
+impl Debug for Instance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{} instance>", self.class.name)
+    }
+}
@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::Value;
+
+#[derive(Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Result<Value, String> {
+        if let Some(value) = self.values.get(name) {
+            return Ok(value.clone());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name),
+            None => Err(undefined(name)),
+        }
+    }
+
+    pub fn assign(&mut self, name: &str, value: Value) -> Result<(), String> {
+        if let Some(slot) = self.values.get_mut(name) {
+            *slot = value;
+            return Ok(());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(undefined(name)),
+        }
+    }
+
+    // `depth` comes from the resolver, counted in enclosing hops from here.
+    pub fn get_at(&self, depth: usize, name: &str) -> Result<Value, String> {
+        match (depth, &self.enclosing) {
+            (0, _) => self
+                .values
+                .get(name)
+                .cloned()
+                .ok_or_else(|| undefined(name)),
+            (_, Some(enclosing)) => enclosing.borrow().get_at(depth - 1, name),
+            (_, None) => Err(undefined(name)),
+        }
+    }
+
+    pub fn assign_at(&mut self, depth: usize, name: &str, value: Value) -> Result<(), String> {
+        match (depth, &self.enclosing) {
+            (0, _) => match self.values.get_mut(name) {
+                Some(slot) => {
+                    *slot = value;
+                    Ok(())
+                }
+                None => Err(undefined(name)),
+            },
+            (_, Some(enclosing)) => enclosing.borrow_mut().assign_at(depth - 1, name, value),
+            (_, None) => Err(undefined(name)),
+        }
+    }
+}
+
+fn undefined(name: &str) -> String {
+    format!("Undefined variable '{}'.", name)
+}
@@ -0,0 +1,708 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::parser::{Expr, FunctionDecl, Stmt};
+use crate::tokenizer::Token;
+
+pub use class::{Class, Instance};
+pub use environment::Environment;
+pub use function::{Function, NativeFunction};
+pub use value::Value;
+
+mod class;
+mod environment;
+mod function;
+mod native;
+#[cfg(test)]
+mod tests;
+mod value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuntimeError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\n[line {}]", self.message, self.line)
+    }
+}
+
+// How control leaves a statement; anything but `Normal` unwinds enclosing blocks.
+pub enum Flow {
+    Normal,
+    Return(Value),
+    Break,
+    Continue,
+}
+
+pub struct Interpreter {
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    // Where `print` writes.
+    output: Box<dyn Write>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        let mut globals = Environment::new();
+        for native in native::NATIVE_FUNCTIONS {
+            globals.define(native.name, Value::NativeFunction(*native));
+        }
+        let globals = Rc::new(RefCell::new(globals));
+        Self {
+            environment: Rc::clone(&globals),
+            globals,
+            output: Box::new(io::stdout()),
+        }
+    }
+
+    pub fn with_output(output: Box<dyn Write>) -> Self {
+        Self {
+            output,
+            ..Self::new()
+        }
+    }
+
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+        self.execute_all(statements)?;
+        Ok(())
+    }
+
+    fn execute_all(&mut self, statements: &[Stmt]) -> Result<Flow, RuntimeError> {
+        for stmt in statements {
+            match self.execute(stmt)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    pub fn execute(&mut self, stmt: &Stmt) -> Result<Flow, RuntimeError> {
+        match stmt {
+            Stmt::Expression(expr) => {
+                self.evaluate(expr)?;
+            }
+            Stmt::Print { values, line } => {
+                let values = values
+                    .iter()
+                    .map(|value| Ok(self.evaluate(value)?.to_string()))
+                    .collect::<Result<Vec<_>, RuntimeError>>()?;
+                writeln!(self.output, "{}", values.join(" ")).map_err(|error| RuntimeError {
+                    line: *line,
+                    message: error.to_string(),
+                })?;
+            }
+            Stmt::Var {
+                name, initializer, ..
+            } => {
+                let value = match initializer {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => Value::Nil,
+                };
+                self.environment.borrow_mut().define(name, value);
+            }
+            Stmt::Block { statements, .. } => {
+                let environment = Environment::with_enclosing(Rc::clone(&self.environment));
+                return self.execute_block(statements, environment);
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    return self.execute(then_branch);
+                } else if let Some(else_branch) = else_branch {
+                    return self.execute(else_branch);
+                }
+            }
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                ..
+            } => {
+                while self.evaluate(condition)?.is_truthy() {
+                    match self.execute(body)? {
+                        Flow::Normal | Flow::Continue => {}
+                        Flow::Break => break,
+                        flow @ Flow::Return(_) => return Ok(flow),
+                    }
+                    if let Some(increment) = increment {
+                        self.evaluate(increment)?;
+                    }
+                }
+            }
+            Stmt::Function(declaration) => {
+                let function = Function {
+                    declaration: Rc::new(declaration.clone()),
+                    closure: Rc::clone(&self.environment),
+                    is_initializer: false,
+                };
+                self.environment
+                    .borrow_mut()
+                    .define(&declaration.name, Value::Function(Rc::new(function)));
+            }
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+                static_methods,
+                line,
+            } => {
+                let superclass = match superclass {
+                    Some(expr) => match self.evaluate(expr)? {
+                        Value::Class(class) => Some(class),
+                        _ => {
+                            return Err(RuntimeError {
+                                line: *line,
+                                message: "Superclass must be a class.".to_string(),
+                            })
+                        }
+                    },
+                    None => None,
+                };
+
+                // Methods of a subclass close over an extra scope holding `super`.
+                let mut closure = Rc::clone(&self.environment);
+                if let Some(superclass) = &superclass {
+                    let mut environment = Environment::with_enclosing(closure);
+                    environment.define("super", Value::Class(Rc::clone(superclass)));
+                    closure = Rc::new(RefCell::new(environment));
+                }
+
+                let functions = |declarations: &[FunctionDecl], is_static: bool| {
+                    declarations
+                        .iter()
+                        .map(|declaration| {
+                            let function = Function {
+                                declaration: Rc::new(declaration.clone()),
+                                closure: Rc::clone(&closure),
+                                is_initializer: !is_static && declaration.name == "init",
+                            };
+                            (declaration.name.clone(), function)
+                        })
+                        .collect::<HashMap<_, _>>()
+                };
+                let class = Class {
+                    name: name.clone(),
+                    superclass,
+                    methods: functions(methods, false),
+                    static_methods: functions(static_methods, true),
+                };
+                self.environment
+                    .borrow_mut()
+                    .define(name, Value::Class(Rc::new(class)));
+            }
+            Stmt::Return { value, .. } => {
+                let value = match value {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => Value::Nil,
+                };
+                return Ok(Flow::Return(value));
+            }
+            Stmt::Break { .. } => return Ok(Flow::Break),
+            Stmt::Continue { .. } => return Ok(Flow::Continue),
+        }
+        Ok(Flow::Normal)
+    }
+
+    pub fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        environment: Environment,
+    ) -> Result<Flow, RuntimeError> {
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+        let result = self.execute_all(statements);
+        self.environment = previous;
+        result
+    }
+
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        match expr {
+            Expr::Literal { value, .. } => Ok(Value::from(value)),
+            Expr::Grouping { expression, .. } => self.evaluate(expression),
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                // Yields the deciding operand itself, not a coerced bool.
+                let left = self.evaluate(left)?;
+                let short_circuits = match operator {
+                    Token::Or | Token::PipePipe => left.is_truthy(),
+                    _ => !left.is_truthy(),
+                };
+                if short_circuits {
+                    Ok(left)
+                } else {
+                    self.evaluate(right)
+                }
+            }
+            Expr::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    self.evaluate(then_branch)
+                } else {
+                    self.evaluate(else_branch)
+                }
+            }
+            Expr::Variable { name, line, depth } => {
+                self.look_up(name, *depth).map_err(|message| RuntimeError {
+                    line: *line,
+                    message,
+                })
+            }
+            Expr::Assign {
+                name,
+                value,
+                line,
+                depth,
+            } => {
+                let value = self.evaluate(value)?;
+                let result = match depth {
+                    Some(depth) => {
+                        self.environment
+                            .borrow_mut()
+                            .assign_at(*depth, name, value.clone())
+                    }
+                    None => self.globals.borrow_mut().assign(name, value.clone()),
+                };
+                result.map_err(|message| RuntimeError {
+                    line: *line,
+                    message,
+                })?;
+                Ok(value)
+            }
+            Expr::Call { .. }
+            | Expr::Index { .. }
+            | Expr::Get { .. }
+            | Expr::OptionalGet { .. } => Ok(self.evaluate_chain(expr)?.unwrap_or(Value::Nil)),
+            Expr::List { elements, .. } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| self.evaluate(element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Value::List(Rc::new(RefCell::new(elements))))
+            }
+            Expr::Map { entries, line } => {
+                let mut map = HashMap::new();
+                for (key, value) in entries {
+                    let key = map_key(&self.evaluate(key)?).map_err(|message| RuntimeError {
+                        line: *line,
+                        message,
+                    })?;
+                    map.insert(key, self.evaluate(value)?);
+                }
+                Ok(Value::Map(Rc::new(RefCell::new(map))))
+            }
+            Expr::IndexSet {
+                object,
+                index,
+                value,
+                line,
+            } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                let value = self.evaluate(value)?;
+                index_set(&object, &index, value.clone()).map_err(|message| RuntimeError {
+                    line: *line,
+                    message,
+                })?;
+                Ok(value)
+            }
+            Expr::Set {
+                object,
+                name,
+                value,
+                line,
+            } => {
+                let Value::Instance(instance) = self.evaluate(object)? else {
+                    return Err(RuntimeError {
+                        line: *line,
+                        message: "Only instances have fields.".to_string(),
+                    });
+                };
+                let value = self.evaluate(value)?;
+                instance
+                    .borrow_mut()
+                    .fields
+                    .insert(name.clone(), value.clone());
+                Ok(value)
+            }
+            Expr::CompoundSet {
+                object,
+                name,
+                operator,
+                value,
+                line,
+            } => {
+                let Value::Instance(instance) = self.evaluate(object)? else {
+                    return Err(RuntimeError {
+                        line: *line,
+                        message: "Only instances have fields.".to_string(),
+                    });
+                };
+                let current = self.get_property(Rc::clone(&instance), name, *line)?;
+                let value = self.evaluate(value)?;
+                let value = binary(operator, current, value).map_err(|message| RuntimeError {
+                    line: *line,
+                    message,
+                })?;
+                instance
+                    .borrow_mut()
+                    .fields
+                    .insert(name.clone(), value.clone());
+                Ok(value)
+            }
+            Expr::This { line, depth } => {
+                self.look_up("this", *depth)
+                    .map_err(|message| RuntimeError {
+                        line: *line,
+                        message,
+                    })
+            }
+            Expr::Super {
+                method,
+                line,
+                depth,
+            } => {
+                // `this` is bound one scope inside the one holding `super`.
+                let depth = depth.expect("the resolver always binds 'super'");
+                let superclass = self.environment.borrow().get_at(depth, "super");
+                let this = self.environment.borrow().get_at(depth - 1, "this");
+                let (Ok(Value::Class(superclass)), Ok(this)) = (superclass, this) else {
+                    unreachable!("the parser only allows 'super' inside subclass instance methods");
+                };
+                match superclass.find_method(method) {
+                    Some(method) => self.access(method.bind(this)),
+                    None => Err(RuntimeError {
+                        line: *line,
+                        message: format!("Undefined property '{}'.", method),
+                    }),
+                }
+            }
+            Expr::Unary {
+                operator,
+                right,
+                line,
+            } => {
+                let right = self.evaluate(right)?;
+                match (operator, right) {
+                    (Token::Minus, Value::Number(n)) => Ok(Value::Number(-n)),
+                    (Token::Minus, _) => Err(RuntimeError {
+                        line: *line,
+                        message: "Operand must be a number.".to_string(),
+                    }),
+                    (Token::Bang, value) => Ok(Value::Bool(!value.is_truthy())),
+                    _ => unreachable!("parser only builds unary '-' and '!'"),
+                }
+            }
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                line,
+            } => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                binary(operator, left, right).map_err(|message| RuntimeError {
+                    line: *line,
+                    message,
+                })
+            }
+        }
+    }
+
+    fn look_up(&self, name: &str, depth: Option<usize>) -> Result<Value, String> {
+        match depth {
+            Some(depth) => self.environment.borrow().get_at(depth, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    // Property access, indexing and calls, evaluated as one chain. `None`
+    // means an `?.` met nil, which skips everything after it in the chain.
+    fn evaluate_chain(&mut self, expr: &Expr) -> Result<Option<Value>, RuntimeError> {
+        match expr {
+            Expr::Get { object, name, line } => match self.evaluate_chain(object)? {
+                Some(receiver) => self.get(receiver, name, *line).map(Some),
+                None => Ok(None),
+            },
+            Expr::OptionalGet { object, name, line } => match self.evaluate_chain(object)? {
+                Some(Value::Nil) | None => Ok(None),
+                Some(receiver) => self.get(receiver, name, *line).map(Some),
+            },
+            Expr::Index {
+                object,
+                index,
+                line,
+            } => {
+                let Some(object) = self.evaluate_chain(object)? else {
+                    return Ok(None);
+                };
+                let index = self.evaluate(index)?;
+                index_get(&object, &index)
+                    .map(Some)
+                    .map_err(|message| RuntimeError {
+                        line: *line,
+                        message,
+                    })
+            }
+            Expr::Call {
+                callee,
+                arguments,
+                line,
+            } => {
+                let Some(callee) = self.evaluate_chain(callee)? else {
+                    return Ok(None);
+                };
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.evaluate(argument))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(callee, arguments, *line).map(Some)
+            }
+            _ => self.evaluate(expr).map(Some),
+        }
+    }
+
+    fn get(&mut self, receiver: Value, name: &str, line: usize) -> Result<Value, RuntimeError> {
+        match receiver {
+            Value::Instance(instance) => self.get_property(instance, name, line),
+            Value::Class(class) => match class.find_static_method(name) {
+                Some(method) => self.access(method.clone()),
+                None if class.find_method(name).is_some() => Err(RuntimeError {
+                    line,
+                    message: format!("'{}' is an instance method; call it on an instance.", name),
+                }),
+                None => Err(RuntimeError {
+                    line,
+                    message: format!("Undefined property '{}'.", name),
+                }),
+            },
+            receiver @ Value::String(_) => {
+                match native::STRING_METHODS
+                    .iter()
+                    .find(|method| method.name == name)
+                {
+                    Some(method) => Ok(Value::NativeMethod {
+                        receiver: Box::new(receiver),
+                        method: *method,
+                    }),
+                    None => Err(RuntimeError {
+                        line,
+                        message: format!("Undefined method '{}' on string.", name),
+                    }),
+                }
+            }
+            _ => Err(RuntimeError {
+                line,
+                message: "Only instances have properties.".to_string(),
+            }),
+        }
+    }
+
+    fn call(
+        &mut self,
+        callee: Value,
+        arguments: Vec<Value>,
+        line: usize,
+    ) -> Result<Value, RuntimeError> {
+        let arity = match &callee {
+            Value::Function(function) => function.arity(),
+            Value::NativeFunction(native) | Value::NativeMethod { method: native, .. } => {
+                native.arity
+            }
+            Value::Class(class) => class.arity(),
+            _ => {
+                return Err(RuntimeError {
+                    line,
+                    message: "Can only call functions and classes.".to_string(),
+                })
+            }
+        };
+        if arguments.len() != arity {
+            return Err(RuntimeError {
+                line,
+                message: format!("Expected {} arguments but got {}.", arity, arguments.len()),
+            });
+        }
+        match callee {
+            Value::Function(function) => self.call_function(&function, arguments),
+            Value::NativeFunction(native) => {
+                (native.function)(&arguments).map_err(|error| RuntimeError { line, ..error })
+            }
+            // The receiver is passed as an extra first argument.
+            Value::NativeMethod { receiver, method } => {
+                let arguments = std::iter::once(*receiver)
+                    .chain(arguments)
+                    .collect::<Vec<_>>();
+                (method.function)(&arguments).map_err(|error| RuntimeError { line, ..error })
+            }
+            Value::Class(class) => {
+                let instance =
+                    Value::Instance(Rc::new(RefCell::new(Instance::new(Rc::clone(&class)))));
+                if let Some(initializer) = class.find_method("init") {
+                    self.call_function(&initializer.bind(instance.clone()), arguments)?;
+                }
+                Ok(instance)
+            }
+            _ => unreachable!("non-callables are rejected above"),
+        }
+    }
+
+    fn call_function(
+        &mut self,
+        function: &Function,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let mut environment = Environment::with_enclosing(Rc::clone(&function.closure));
+        for (param, argument) in function.declaration.params.iter().zip(arguments) {
+            environment.define(param, argument);
+        }
+        let flow = self.execute_block(&function.declaration.body, environment)?;
+        if function.is_initializer {
+            // `init` always hands back the instance, even on a bare `return;`.
+            let this = function.closure.borrow().get("this");
+            return Ok(this.expect("bound initializers always define 'this'"));
+        }
+        match flow {
+            Flow::Return(value) => Ok(value),
+            Flow::Normal => Ok(Value::Nil),
+            Flow::Break | Flow::Continue => {
+                unreachable!("the parser rejects 'break' and 'continue' outside loops")
+            }
+        }
+    }
+
+    // Fields shadow methods; a method is bound to the instance it was read from.
+    fn get_property(
+        &mut self,
+        instance: Rc<RefCell<Instance>>,
+        name: &str,
+        line: usize,
+    ) -> Result<Value, RuntimeError> {
+        let value = instance.borrow().fields.get(name).cloned();
+        if let Some(value) = value {
+            return Ok(value);
+        }
+        let class = instance.borrow().class.clone();
+        match class.find_method(name) {
+            Some(method) => self.access(method.bind(Value::Instance(instance))),
+            None if class.find_static_method(name).is_some() => Err(RuntimeError {
+                line,
+                message: format!("'{}' is a static method; call it on the class.", name),
+            }),
+            None => Err(RuntimeError {
+                line,
+                message: format!("Undefined property '{}'.", name),
+            }),
+        }
+    }
+
+    // Reading a getter runs it; any other method is handed back bound.
+    fn access(&mut self, method: Function) -> Result<Value, RuntimeError> {
+        match method.declaration.getter {
+            true => self.call_function(&method, Vec::new()),
+            false => Ok(Value::Function(Rc::new(method))),
+        }
+    }
+}
+
+fn index_get(object: &Value, index: &Value) -> Result<Value, String> {
+    match object {
+        Value::List(elements) => {
+            let elements = elements.borrow();
+            Ok(elements[list_index(index, elements.len())?].clone())
+        }
+        Value::Map(map) => {
+            let key = map_key(index)?;
+            map.borrow()
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| format!("Undefined key '{}'.", key))
+        }
+        _ => Err("Only lists and maps can be indexed.".to_string()),
+    }
+}
+
+// Lists only replace existing elements; maps also gain new keys.
+fn index_set(object: &Value, index: &Value, value: Value) -> Result<(), String> {
+    match object {
+        Value::List(elements) => {
+            let mut elements = elements.borrow_mut();
+            let i = list_index(index, elements.len())?;
+            elements[i] = value;
+        }
+        Value::Map(map) => {
+            map.borrow_mut().insert(map_key(index)?, value);
+        }
+        _ => return Err("Only lists and maps can be indexed.".to_string()),
+    }
+    Ok(())
+}
+
+fn map_key(key: &Value) -> Result<String, String> {
+    match key {
+        Value::String(key) => Ok(key.clone()),
+        _ => Err("Map keys must be strings.".to_string()),
+    }
+}
+
+fn list_index(index: &Value, len: usize) -> Result<usize, String> {
+    let Value::Number(n) = index else {
+        return Err("List index must be a number.".to_string());
+    };
+    if n.fract() != 0.0 || *n < 0.0 {
+        return Err("List index must be a non-negative integer.".to_string());
+    }
+    if *n >= len as f64 {
+        return Err(format!("List index {} out of range for length {}.", n, len));
+    }
+    Ok(*n as usize)
+}
+
+fn binary(operator: &Token, left: Value, right: Value) -> Result<Value, String> {
+    let value = match (operator, left, right) {
+        (Token::EqualEqual, l, r) => Value::Bool(l == r),
+        (Token::BangEqual, l, r) => Value::Bool(l != r),
+        // `+` never coerces: a number and a string don't concatenate.
+        (Token::Plus, Value::Number(l), Value::Number(r)) => Value::Number(l + r),
+        (Token::Plus, Value::String(l), Value::String(r)) => Value::String(l + &r),
+        (Token::Plus, _, _) => {
+            return Err("Operands must be two numbers or two strings.".to_string())
+        }
+        (operator, Value::Number(l), Value::Number(r)) => match operator {
+            Token::Minus => Value::Number(l - r),
+            Token::Star => Value::Number(l * r),
+            Token::Slash => Value::Number(l / r),
+            Token::Percent => Value::Number(l % r),
+            Token::Greater => Value::Bool(l > r),
+            Token::GreaterEqual => Value::Bool(l >= r),
+            Token::Less => Value::Bool(l < r),
+            Token::LessEqual => Value::Bool(l <= r),
+            _ => unreachable!("parser only builds known binary operators"),
+        },
+        _ => return Err("Operands must be numbers.".to_string()),
+    };
+    Ok(value)
+}
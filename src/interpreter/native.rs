@@ -0,0 +1,175 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{NativeFunction, RuntimeError, Value};
+
+pub const NATIVE_FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "clock",
+        arity: 0,
+        function: clock,
+    },
+    NativeFunction {
+        name: "get",
+        arity: 2,
+        function: get,
+    },
+    NativeFunction {
+        name: "floor",
+        arity: 1,
+        function: floor,
+    },
+    NativeFunction {
+        name: "ceil",
+        arity: 1,
+        function: ceil,
+    },
+    NativeFunction {
+        name: "round",
+        arity: 1,
+        function: round,
+    },
+    NativeFunction {
+        name: "sqrt",
+        arity: 1,
+        function: sqrt,
+    },
+    NativeFunction {
+        name: "len",
+        arity: 1,
+        function: len,
+    },
+    NativeFunction {
+        name: "type",
+        arity: 1,
+        function: type_of,
+    },
+];
+
+// Methods on strings. `arity` doesn't count the receiver, which is passed as
+// the first argument.
+pub const STRING_METHODS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "length",
+        arity: 0,
+        function: length,
+    },
+    NativeFunction {
+        name: "upper",
+        arity: 0,
+        function: upper,
+    },
+    NativeFunction {
+        name: "substring",
+        arity: 2,
+        function: substring,
+    },
+];
+
+fn clock(_: &[Value]) -> Result<Value, RuntimeError> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(Value::Number(elapsed.as_secs_f64()))
+}
+
+// `get(map, key)`: like `map[key]`, but a missing key gives `nil`.
+fn get(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    match (&arguments[0], &arguments[1]) {
+        (Value::Map(map), Value::String(key)) => {
+            Ok(map.borrow().get(key).cloned().unwrap_or(Value::Nil))
+        }
+        _ => Err(RuntimeError {
+            line: 0,
+            message: "get() expects a map and a string key.".to_string(),
+        }),
+    }
+}
+
+fn number(name: &str, arguments: &[Value]) -> Result<f64, RuntimeError> {
+    match arguments[0] {
+        Value::Number(n) => Ok(n),
+        _ => Err(RuntimeError {
+            line: 0,
+            message: format!("{}() expects a number.", name),
+        }),
+    }
+}
+
+fn floor(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(number("floor", arguments)?.floor()))
+}
+
+fn ceil(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(number("ceil", arguments)?.ceil()))
+}
+
+// Halfway cases round away from zero: `round(-2.5)` is `-3`.
+fn round(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(number("round", arguments)?.round()))
+}
+
+// Negative numbers give `NaN`, as in IEEE arithmetic.
+fn sqrt(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(number("sqrt", arguments)?.sqrt()))
+}
+
+// Strings count characters, not bytes, like `length`.
+fn len(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    let len = match &arguments[0] {
+        Value::String(s) => s.chars().count(),
+        Value::List(list) => list.borrow().len(),
+        Value::Map(map) => map.borrow().len(),
+        _ => {
+            return Err(RuntimeError {
+                line: 0,
+                message: "len() requires a string, list, or map.".to_string(),
+            })
+        }
+    };
+    Ok(Value::Number(len as f64))
+}
+
+fn type_of(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::String(arguments[0].type_name().to_string()))
+}
+
+fn receiver(arguments: &[Value]) -> &str {
+    match &arguments[0] {
+        Value::String(s) => s,
+        _ => unreachable!("string methods are only looked up on strings"),
+    }
+}
+
+// Lengths and positions count characters, not bytes.
+fn length(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(receiver(arguments).chars().count() as f64))
+}
+
+fn upper(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::String(receiver(arguments).to_uppercase()))
+}
+
+// `substring(start, end)`: characters from `start` up to, not including, `end`.
+fn substring(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    let s = receiver(arguments);
+    let len = s.chars().count();
+    let error = |message: String| RuntimeError { line: 0, message };
+    let (Value::Number(start), Value::Number(end)) = (&arguments[1], &arguments[2]) else {
+        return Err(error("substring() indices must be numbers.".to_string()));
+    };
+    if [start, end].iter().any(|n| n.fract() != 0.0 || **n < 0.0) {
+        return Err(error(
+            "substring() indices must be non-negative integers.".to_string(),
+        ));
+    }
+    let (start, end) = (*start as usize, *end as usize);
+    if start > end || end > len {
+        return Err(error(format!(
+            "substring() range {}..{} out of bounds for length {}.",
+            start, end, len
+        )));
+    }
+    Ok(Value::String(
+        s.chars().skip(start).take(end - start).collect(),
+    ))
+}
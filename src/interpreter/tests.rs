@@ -0,0 +1,504 @@
+use super::*;
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+use crate::tokenizer::tokenize_checked;
+
+// Collects what `print` writes so tests can inspect it.
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Runs a program and returns everything it printed.
+fn run(source: &str) -> Result<String, RuntimeError> {
+    let mut statements = Parser::new(tokenize_checked(source).0)
+        .parse_program()
+        .expect("source should parse");
+    Resolver::new()
+        .resolve(&mut statements)
+        .expect("source should resolve");
+    let output = Output::default();
+    Interpreter::with_output(Box::new(output.clone())).interpret(&statements)?;
+    let printed = output.0.borrow();
+    Ok(String::from_utf8_lossy(&printed).into_owned())
+}
+
+fn printed(source: &str) -> String {
+    run(source).expect("source should run")
+}
+
+fn evaluate(source: &str) -> Result<Value, RuntimeError> {
+    let expr = Parser::new(tokenize_checked(source).0)
+        .parse()
+        .expect("source should parse");
+    Interpreter::new().evaluate(&expr)
+}
+
+// The value as the `evaluate` command prints it.
+fn evaluated(source: &str) -> String {
+    evaluate(source)
+        .expect("source should evaluate")
+        .to_string()
+}
+
+#[test]
+fn evaluates_arithmetic_strings_and_negation() {
+    assert_eq!(evaluated("(3 + 4) * 2"), "14");
+    assert_eq!(evaluated("\"a\" + \"b\""), "ab");
+    assert_eq!(evaluated("!nil"), "true");
+}
+
+#[test]
+fn truthiness_and_equality() {
+    assert_eq!(evaluated("!!0"), "true");
+    assert_eq!(evaluated("!!\"\""), "true");
+    assert_eq!(evaluated("1 == \"1\""), "false");
+    assert_eq!(evaluated("nil == nil"), "true");
+    assert_eq!(evaluated("nil == false"), "false");
+}
+
+#[test]
+fn type_errors_are_runtime_errors() {
+    let message = |source| evaluate(source).unwrap_err().message;
+    assert_eq!(message("-\"a\""), "Operand must be a number.");
+    assert_eq!(message("1 - \"a\""), "Operands must be numbers.");
+    assert_eq!(message("\"a\" < \"b\""), "Operands must be numbers.");
+}
+
+#[test]
+fn print_statements_write_lines() {
+    assert_eq!(printed("print 1 + 2;\nprint \"done\";"), "3\ndone\n");
+}
+
+#[test]
+fn global_variables() {
+    assert_eq!(printed("var a = 1; print a;"), "1\n");
+    assert_eq!(printed("var a = 1; var a = \"two\"; print a;"), "two\n");
+    assert_eq!(printed("var a; print a;"), "nil\n");
+    assert_eq!(
+        run("print missing;").unwrap_err(),
+        RuntimeError {
+            line: 1,
+            message: "Undefined variable 'missing'.".to_string()
+        }
+    );
+}
+
+#[test]
+fn blocks_shadow_and_restore() {
+    let source = "var a = \"outer\"; { var a = \"inner\"; print a; } print a;";
+    assert_eq!(printed(source), "inner\nouter\n");
+}
+
+#[test]
+fn block_locals_do_not_leak() {
+    let error = run("{ var local = 1; } print local;").unwrap_err();
+    assert_eq!(error.message, "Undefined variable 'local'.");
+}
+
+#[test]
+fn if_and_while() {
+    assert_eq!(
+        printed("if (false) print \"skipped\"; print \"after\";"),
+        "after\n"
+    );
+    assert_eq!(
+        printed("if (nil) print \"then\"; else print \"else\";"),
+        "else\n"
+    );
+    assert_eq!(
+        printed("var n = 3; while (n > 0) { print n; n = n - 1; }"),
+        "3\n2\n1\n"
+    );
+}
+
+#[test]
+fn for_loops() {
+    assert_eq!(
+        printed("for (var i = 0; i < 3; i = i + 1) print i;"),
+        "0\n1\n2\n"
+    );
+    // A missing condition means the loop only ends by leaving it.
+    assert_eq!(
+        printed("for (var i = 0;; i = i + 1) { if (i == 2) break; print i; }"),
+        "0\n1\n"
+    );
+}
+
+#[test]
+fn functions_closures_and_recursion() {
+    assert_eq!(
+        printed("fun add(a, b) { return a + b; } print add(1, 2);"),
+        "3\n"
+    );
+    let counter = "
+        fun makeCounter() {
+            var count = 0;
+            fun increment() { count = count + 1; return count; }
+            return increment;
+        }
+        var counter = makeCounter();
+        counter();
+        print counter();";
+    assert_eq!(printed(counter), "2\n");
+    let factorial = "
+        fun factorial(n) { if (n <= 1) return 1; return n * factorial(n - 1); }
+        print factorial(5);";
+    assert_eq!(printed(factorial), "120\n");
+}
+
+#[test]
+fn clock_returns_non_decreasing_numbers() {
+    assert!(matches!(evaluate("clock()"), Ok(Value::Number(_))));
+    assert_eq!(
+        printed("var a = clock(); var b = clock(); print b >= a;"),
+        "true\n"
+    );
+}
+
+#[test]
+fn methods_read_fields() {
+    let source = "
+        class Box {
+            init(value) { this.value = value; }
+            get() { return this.value; }
+        }
+        print Box(3).get();
+        print Box(1);";
+    assert_eq!(printed(source), "3\nBox instance\n");
+}
+
+#[test]
+fn undefined_property_is_a_runtime_error() {
+    let error = run("class A {} print A().missing;").unwrap_err();
+    assert_eq!(error.message, "Undefined property 'missing'.");
+}
+
+#[test]
+fn overridden_method_calls_super() {
+    let source = "
+        class A { name() { return \"A\"; } }
+        class B < A { name() { return \"B of \" + super.name(); } }
+        print B().name();";
+    assert_eq!(printed(source), "B of A\n");
+}
+
+#[test]
+fn superclass_must_be_a_class() {
+    let error = run("var NotAClass = 1; class B < NotAClass {}").unwrap_err();
+    assert_eq!(error.message, "Superclass must be a class.");
+}
+
+#[test]
+fn closures_keep_the_binding_they_were_resolved_to() {
+    let source = "
+        var a = \"global\";
+        {
+            fun showA() { print a; }
+            showA();
+            var a = \"block\";
+            showA();
+        }";
+    assert_eq!(printed(source), "global\nglobal\n");
+}
+
+#[test]
+fn modulo() {
+    assert_eq!(evaluated("7 % 3 == 1"), "true");
+}
+
+#[test]
+fn compound_assignment() {
+    assert_eq!(printed("var a = 1; a += 2; print a;"), "3\n");
+    assert_eq!(printed("var s = \"a\"; s += \"b\"; print s;"), "ab\n");
+}
+
+#[test]
+fn break_leaves_a_loop_early() {
+    let source = "var i = 0; while (true) { if (i == 2) break; print i; i = i + 1; }";
+    assert_eq!(printed(source), "0\n1\n");
+}
+
+#[test]
+fn continue_skips_the_rest_of_an_iteration() {
+    let source = "
+        var i = 0;
+        while (i < 4) {
+            i = i + 1;
+            if (i == 2) continue;
+            print i;
+        }";
+    assert_eq!(printed(source), "1\n3\n4\n");
+}
+
+#[test]
+fn continue_in_a_for_loop_still_runs_the_increment() {
+    let source = "for (var i = 0; i < 4; i = i + 1) { if (i == 1) continue; print i; }";
+    assert_eq!(printed(source), "0\n2\n3\n");
+}
+
+#[test]
+fn plus_adds_numbers_or_concatenates_strings() {
+    assert_eq!(evaluated("1 + 2"), "3");
+    assert_eq!(evaluated("\"con\" + \"cat\""), "concat");
+    for source in ["1 + \"a\"", "\"a\" + 1", "nil + nil"] {
+        assert_eq!(
+            evaluate(source).unwrap_err().message,
+            "Operands must be two numbers or two strings."
+        );
+    }
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    let source = "
+        var called = false;
+        fun sideEffect() { called = true; return true; }
+        print false and sideEffect();
+        print true or sideEffect();
+        print called;";
+    assert_eq!(printed(source), "false\ntrue\nfalse\n");
+    assert_eq!(evaluated("nil or 5"), "5");
+    assert_eq!(evaluated("\"a\" and 0"), "0");
+}
+
+#[test]
+fn getters_run_on_access_but_methods_need_a_call() {
+    let source = "
+        class Circle {
+            init(radius) { this.radius = radius; }
+            area { return 3 * this.radius * this.radius; }
+            diameter() { return 2 * this.radius; }
+        }
+        var circle = Circle(2);
+        print circle.area;
+        print circle.diameter();
+        print circle.diameter;";
+    assert_eq!(printed(source), "12\n4\n<fn diameter>\n");
+    assert_eq!(
+        run("class A { m() { return 1; } } A().m.x;")
+            .unwrap_err()
+            .message,
+        "Only instances have properties."
+    );
+}
+
+#[test]
+fn static_methods_are_called_on_the_class() {
+    let source = "
+        class A {
+            static make() { return A(); }
+            name() { return \"a\"; }
+        }
+        class B < A {
+            name() { return \"b\" + super.name(); }
+        }
+        print A.make().name();
+        print B().name();";
+    assert_eq!(printed(source), "a\nba\n");
+    assert_eq!(
+        run("class A { static f() {} } A().f();")
+            .unwrap_err()
+            .message,
+        "'f' is a static method; call it on the class."
+    );
+}
+
+#[test]
+fn lists_are_indexed_from_zero() {
+    assert_eq!(
+        printed("var a = [1, \"two\", nil]; print a[1]; print [[3]][0][0];"),
+        "two\n3\n"
+    );
+    assert_eq!(
+        run("var a = [1, 2]; print a[2];").unwrap_err().message,
+        "List index 2 out of range for length 2."
+    );
+    assert_eq!(
+        run("print [1][0.5];").unwrap_err().message,
+        "List index must be a non-negative integer."
+    );
+}
+
+#[test]
+fn index_assignment_mutates_in_place() {
+    assert_eq!(
+        printed("var a = [1, 2]; var b = a; b[0] = 5; print a;"),
+        "[5, 2]\n"
+    );
+    assert_eq!(
+        run("var a = [1]; a[1] = 2;").unwrap_err().message,
+        "List index 1 out of range for length 1."
+    );
+}
+
+#[test]
+fn self_referencing_collections_print_a_placeholder() {
+    assert_eq!(printed("var a = [1]; a[0] = a; print a;"), "[[...]]\n");
+    assert_eq!(
+        printed("var m = {\"k\": 1}; m[\"k\"] = [m]; print m;"),
+        "{k: [{...}]}\n"
+    );
+    // The same list twice isn't a cycle.
+    assert_eq!(printed("var a = [1]; print [a, a];"), "[[1], [1]]\n");
+}
+
+#[test]
+fn numbers_display_without_a_needless_fraction() {
+    assert_eq!(Value::Number(5.0).to_string(), "5");
+    assert_eq!(Value::Number(5.5).to_string(), "5.5");
+    assert_eq!(Value::Number(-0.0).to_string(), "-0");
+    assert_eq!(Value::String("five".to_string()).to_string(), "five");
+}
+
+#[test]
+fn print_separates_values_with_spaces() {
+    assert_eq!(printed("print 1, \"two\", nil;"), "1 two nil\n");
+}
+
+#[test]
+fn map_literals_are_indexed_by_key() {
+    let source = "
+        var m = {\"a\": 1, \"b\": [2], \"c\": {}};
+        print m[\"a\"];
+        print m[\"b\"][0];
+        print m;
+        print get(m, \"z\");
+        { var block = 1; }";
+    assert_eq!(printed(source), "1\n2\n{a: 1, b: [2], c: {}}\nnil\n");
+    assert_eq!(
+        run("var m = {};\nprint m[\"z\"];").unwrap_err().to_string(),
+        "Undefined key 'z'.\n[line 2]"
+    );
+}
+
+#[test]
+fn string_methods_dispatch_on_the_receiver() {
+    assert_eq!(evaluated("\"héllo\".length()"), "5");
+    assert_eq!(evaluated("\"hello\".upper()"), "HELLO");
+    assert_eq!(evaluated("\"hello\".substring(1, 3)"), "el");
+    assert_eq!(evaluated("\"hello\".substring(5, 5)"), "");
+    let error = |source| evaluate(source).unwrap_err().message;
+    assert_eq!(
+        error("\"hello\".substring(2, 9)"),
+        "substring() range 2..9 out of bounds for length 5."
+    );
+    assert_eq!(
+        error("\"hello\".substring(1.5, 2)"),
+        "substring() indices must be non-negative integers."
+    );
+    assert_eq!(
+        error("\"hello\".upper(1)"),
+        "Expected 0 arguments but got 1."
+    );
+    assert_eq!(
+        error("\"hello\".shout()"),
+        "Undefined method 'shout' on string."
+    );
+}
+
+#[test]
+fn numeric_natives_take_one_number() {
+    assert_eq!(evaluated("floor(-1.5)"), "-2");
+    assert_eq!(evaluated("ceil(1.2)"), "2");
+    assert_eq!(evaluated("round(2.5)"), "3");
+    assert_eq!(evaluated("round(-2.5)"), "-3");
+    assert_eq!(evaluated("sqrt(16)"), "4");
+    assert_eq!(
+        run("print 1;\nprint sqrt(\"16\");")
+            .unwrap_err()
+            .to_string(),
+        "sqrt() expects a number.\n[line 2]"
+    );
+    assert_eq!(
+        evaluate("floor(1, 2)").unwrap_err().message,
+        "Expected 1 arguments but got 2."
+    );
+}
+
+#[test]
+fn functions_pass_through_variables_by_reference() {
+    let source = "
+        fun counter() {
+            var count = 0;
+            fun next() { count = count + 1; return count; }
+            return next;
+        }
+        var a = counter();
+        var b = a;
+        var c = b;
+        a();
+        b();
+        print c();
+        print a == c;";
+    assert_eq!(printed(source), "3\ntrue\n");
+}
+
+#[test]
+fn assignment_is_a_right_associative_expression() {
+    assert_eq!(
+        printed("var a; var b; a = b = 5; print a; print b; print a = 3;"),
+        "5\n5\n3\n"
+    );
+}
+
+#[test]
+fn undefined_variable_reports_the_line_of_its_use() {
+    assert_eq!(
+        run("var a = 1;\nprint a;\n\nprint missing;")
+            .unwrap_err()
+            .to_string(),
+        "Undefined variable 'missing'.\n[line 4]"
+    );
+}
+
+#[test]
+fn optional_chaining_short_circuits_on_nil() {
+    let source = "
+        class Node { init(next) { this.next = next; } }
+        var missing = nil;
+        print missing?.next.next.next;
+        print Node(Node(nil)).next?.next;";
+    assert_eq!(printed(source), "nil\nnil\n");
+    assert_eq!(
+        run("print 1?.field;").unwrap_err().message,
+        "Only instances have properties."
+    );
+}
+
+#[test]
+fn len_counts_characters_elements_and_entries() {
+    assert_eq!(evaluated("len(\"héllo\")"), "5");
+    assert_eq!(evaluated("len([1, 2, 3])"), "3");
+    assert_eq!(evaluated("len({\"a\": 1, \"b\": 2})"), "2");
+    assert_eq!(evaluated("len(\"\")"), "0");
+    assert_eq!(
+        evaluate("len(42)").unwrap_err().message,
+        "len() requires a string, list, or map."
+    );
+}
+
+#[test]
+fn type_names_every_kind_of_value() {
+    let source = "
+        fun f() {}
+        class C { m() {} }
+        print type(1), type(\"s\"), type(true), type(nil);
+        print type(f), type(clock), type(\"s\".upper), type(C().m);
+        print type(C), type(C()), type([]), type({});";
+    assert_eq!(
+        printed(source),
+        "number string bool nil\n\
+         function function function function\n\
+         class instance list map\n"
+    );
+}
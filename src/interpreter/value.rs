@@ -0,0 +1,174 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::rc::Rc;
+
+use crate::parser::Literal;
+
+use super::{Class, Function, Instance, NativeFunction};
+
+// Cloning a value never copies a function body, class or collection: those
+// sit behind an `Rc`, so passing them around only bumps a reference count.
+#[derive(Clone)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Nil,
+    Function(Rc<Function>),
+    NativeFunction(NativeFunction),
+    // A built-in method looked up on a value, such as `"abc".upper`.
+    NativeMethod {
+        receiver: Box<Value>,
+        method: NativeFunction,
+    },
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<HashMap<String, Value>>>),
+}
+
+// Equality never coerces: values of different types are simply unequal.
+// Classes, instances, lists and maps compare by identity, everything else by content.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Number(l), Value::Number(r)) => l == r,
+            (Value::String(l), Value::String(r)) => l == r,
+            (Value::Bool(l), Value::Bool(r)) => l == r,
+            (Value::Nil, Value::Nil) => true,
+            (Value::Function(l), Value::Function(r)) => l == r,
+            (Value::NativeFunction(l), Value::NativeFunction(r)) => l == r,
+            (
+                Value::NativeMethod {
+                    receiver: l,
+                    method: lm,
+                },
+                Value::NativeMethod {
+                    receiver: r,
+                    method: rm,
+                },
+            ) => l == r && lm == rm,
+            (Value::Class(l), Value::Class(r)) => Rc::ptr_eq(l, r),
+            (Value::Instance(l), Value::Instance(r)) => Rc::ptr_eq(l, r),
+            (Value::List(l), Value::List(r)) => Rc::ptr_eq(l, r),
+            (Value::Map(l), Value::Map(r)) => Rc::ptr_eq(l, r),
+            _ => false,
+        }
+    }
+}
+
+impl Value {
+    // Only `false` and `nil` are falsy; `0` and `""` are truthy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    // What `type(value)` returns. Natives and bound methods are functions too.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Bool(_) => "bool",
+            Value::Nil => "nil",
+            Value::Function(_) | Value::NativeFunction(_) | Value::NativeMethod { .. } => {
+                "function"
+            }
+            Value::Class(_) => "class",
+            Value::Instance(_) => "instance",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+}
+
+impl From<&Literal> for Value {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::Number(n) => Value::Number(*n),
+            Literal::String(s) => Value::String(s.clone()),
+            Literal::Bool(b) => Value::Bool(*b),
+            Literal::Nil => Value::Nil,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write(f, &mut Vec::new())
+    }
+}
+
+// Strings are quoted; everything else debugs as it displays, which keeps
+// self-referencing collections and instances from recursing.
+impl Debug for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", s),
+            value => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Value {
+    // `enclosing` holds the lists and maps being printed around this value, so
+    // one that contains itself prints as `[...]` or `{...}` instead of
+    // overflowing the stack. Shared but acyclic collections print in full.
+    fn write(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        enclosing: &mut Vec<*const ()>,
+    ) -> std::fmt::Result {
+        match self {
+            // Integral numbers print without a fraction (`5`, not `5.0`); the
+            // rest use the shortest representation that round-trips.
+            Value::Number(n) if n.fract() == 0.0 && n.is_finite() => write!(f, "{:.0}", n),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Nil => write!(f, "nil"),
+            Value::Function(function) => write!(f, "<fn {}>", function.declaration.name),
+            Value::NativeFunction(_) => write!(f, "<native fn>"),
+            Value::NativeMethod { method, .. } => write!(f, "<native fn {}>", method.name),
+            Value::Class(class) => write!(f, "{}", class.name),
+            Value::Instance(instance) => write!(f, "{} instance", instance.borrow().class.name),
+            Value::List(elements) => {
+                let id = Rc::as_ptr(elements) as *const ();
+                if enclosing.contains(&id) {
+                    return write!(f, "[...]");
+                }
+                enclosing.push(id);
+                write!(f, "[")?;
+                for (i, element) in elements.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.write(f, enclosing)?;
+                }
+                enclosing.pop();
+                write!(f, "]")
+            }
+            // Sorted by key, so the output doesn't depend on hashing order.
+            Value::Map(map) => {
+                let id = Rc::as_ptr(map) as *const ();
+                if enclosing.contains(&id) {
+                    return write!(f, "{{...}}");
+                }
+                enclosing.push(id);
+                let map = map.borrow();
+                let mut keys = map.keys().collect::<Vec<_>>();
+                keys.sort();
+                write!(f, "{{")?;
+                for (i, key) in keys.into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", key)?;
+                    map[key].write(f, enclosing)?;
+                }
+                enclosing.pop();
+                write!(f, "}}")
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod diagnostics;
+pub mod interpreter;
+pub mod parser;
+pub mod resolver;
+pub mod tokenizer;
+
+pub use tokenizer::{tokenize, Token, TokenizerError};
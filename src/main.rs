@@ -1,44 +1,315 @@
 use std::env;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
 use std::process::ExitCode;
 
-mod tokenizer;
+use interpreter_starter_rust::{diagnostics, interpreter, parser, resolver, tokenizer};
 
 fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
+    if args.len() == 1 || (args.len() == 2 && args[1] == "repl") {
+        return repl();
+    }
     if args.len() < 3 {
-        writeln!(io::stderr(), "Usage: {} tokenize <filename>", args[0]).unwrap();
-        return ExitCode::from(1); // TODO:See lox error codes
+        return usage(&args[0]);
     }
 
     let command = &args[1];
     let filename = &args[2];
+    let options = match Options::parse(&args[3..]) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            return usage(&args[0]);
+        }
+    };
 
     match command.as_str() {
         "tokenize" => {
             let mut return_code = 0;
-            let file_contents = fs::read_to_string(filename).unwrap_or_else(|_| {
-                writeln!(io::stderr(), "Failed to read file {}", filename).unwrap();
-                String::new()
-            });
+            let file_contents = match read_source(filename) {
+                Ok(source) => source,
+                Err(code) => return code,
+            };
 
             let tokens = tokenizer::tokenize(&file_contents);
-            for token in tokens {
-                match token {
-                    tokenizer::Token::Invalid(e) => {
-                        writeln!(io::stderr(), "[line {}] Error: {}", e.line, e.message).unwrap();
-                        return_code = 65;
+            if options.lint {
+                report_lints(&file_contents, &tokens);
+            }
+            let mut error_count = 0;
+            for spanned in &tokens {
+                if let tokenizer::Token::Invalid(e) = &spanned.token {
+                    // `is_none_or` needs Rust 1.82; the project targets 1.77.
+                    #[allow(clippy::unnecessary_map_or)]
+                    let shown = options.max_errors.map_or(true, |max| error_count < max);
+                    if shown {
+                        report_tokenizer_error(&file_contents, e, &options);
                     }
-                    tokenizer::Token::WhiteSpace => {}
-                    _ => println!("{}", token),
+                    error_count += 1;
+                    return_code = 65;
+                }
+            }
+            if let Some(hidden) = options
+                .max_errors
+                .and_then(|max| error_count.checked_sub(max))
+                .filter(|&hidden| hidden > 0)
+            {
+                let noun = if hidden == 1 { "error" } else { "errors" };
+                eprintln!("... and {} more {}.", hidden, noun);
+            }
+            if options.tokens_only {
+                let summary = tokenizer::TokenSummary {
+                    tokens: &tokens,
+                    source: &file_contents,
+                };
+                println!("{}", summary);
+            } else if options.json {
+                println!("{}", tokenizer::to_json(&tokens));
+            } else {
+                println!("{}", tokenizer::TokenList(&tokens));
+            }
+            ExitCode::from(return_code)
+        }
+        "parse" => match parse_expression(filename, &options) {
+            Ok(expr) if options.json => {
+                println!("{}", parser::to_json(&expr));
+                ExitCode::SUCCESS
+            }
+            Ok(expr) => {
+                println!("{}", expr);
+                ExitCode::SUCCESS
+            }
+            Err(code) => code,
+        },
+        "evaluate" => {
+            let expr = match parse_expression(filename, &options) {
+                Ok(expr) => expr,
+                Err(code) => return code,
+            };
+            match interpreter::Interpreter::new().evaluate(&expr) {
+                Ok(value) => {
+                    println!("{}", value);
+                    ExitCode::SUCCESS
+                }
+                Err(error) => {
+                    eprintln!("{}", error);
+                    ExitCode::from(70)
+                }
+            }
+        }
+        "run" => {
+            let statements = match parse_program(filename, &options) {
+                Ok(statements) => statements,
+                Err(code) => return code,
+            };
+            let mut interpreter = interpreter::Interpreter::new();
+            // With `--print-last`, a trailing expression statement is
+            // evaluated on its own so its value can be printed.
+            let result = match statements.split_last() {
+                Some((parser::Stmt::Expression(last), rest)) if options.print_last => interpreter
+                    .interpret(rest)
+                    .and_then(|()| interpreter.evaluate(last))
+                    .map(|value| println!("{}", value)),
+                _ => interpreter.interpret(&statements),
+            };
+            match result {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    ExitCode::from(70)
                 }
             }
-            return ExitCode::from(return_code);
         }
         _ => {
-            writeln!(io::stderr(), "Unknown command: {}", command).unwrap();
-            return ExitCode::from(1); //TODO: See lox error codes
+            eprintln!("Unknown command: {}", command);
+            usage(&args[0])
         }
     }
 }
+
+fn usage(program: &str) -> ExitCode {
+    eprintln!(
+        "Usage: {} <tokenize|parse|evaluate|run> <filename> [options]\n       {} [repl]",
+        program, program
+    );
+    // EX_USAGE
+    ExitCode::from(64)
+}
+
+// Reads one line at a time, keeping globals alive between lines. Errors are
+// reported but never end the session; end of input does.
+fn repl() -> ExitCode {
+    let mut interpreter = interpreter::Interpreter::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        // The prompt goes to stderr so piped output only holds results.
+        eprint!("> ");
+        io::stderr().flush().ok();
+        let Some(Ok(line)) = lines.next() else {
+            return ExitCode::SUCCESS;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let statements = match parse_line(&line) {
+            Ok(statements) => statements,
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("{}", error);
+                }
+                continue;
+            }
+        };
+        // A lone expression echoes its value, like `evaluate` does.
+        let result = match statements.as_slice() {
+            [parser::Stmt::Expression(expr)] => interpreter
+                .evaluate(expr)
+                .map(|value| println!("{}", value)),
+            _ => interpreter.interpret(&statements),
+        };
+        if let Err(error) = result {
+            eprintln!("{}", error);
+        }
+    }
+}
+
+// The trailing `;` is optional at the prompt, so a line that fails to parse
+// is retried with one appended before its errors are reported.
+fn parse_line(line: &str) -> Result<Vec<parser::Stmt>, Vec<String>> {
+    let attempt = |source: &str| {
+        let (tokens, errors) = tokenizer::tokenize_checked(source);
+        if !errors.is_empty() {
+            return Err(errors
+                .iter()
+                .map(|e| format!("[line {}, col {}] Error: {}", e.line, e.column, e.message))
+                .collect::<Vec<_>>());
+        }
+        let mut statements = parser::Parser::new(tokens)
+            .parse_program()
+            .map_err(|errors| errors.iter().map(|e| e.to_string()).collect::<Vec<_>>())?;
+        resolver::Resolver::new()
+            .resolve(&mut statements)
+            .map_err(|errors| errors.iter().map(|e| e.to_string()).collect::<Vec<_>>())?;
+        Ok(statements)
+    };
+    attempt(line).or_else(|errors| attempt(&format!("{};", line)).map_err(|_| errors))
+}
+
+#[derive(Default)]
+struct Options {
+    // `--format json`: tokens or the syntax tree as JSON.
+    json: bool,
+    // `--pretty`: tokenizer errors quote the source line with a caret.
+    pretty: bool,
+    // `--tokens-only`: `tokenize` prints per-type counts instead of tokens.
+    tokens_only: bool,
+    // `--lint`: warn about identifiers that look like miscased keywords.
+    lint: bool,
+    // `--warn-unused`: warn about local variables that are never read.
+    warn_unused: bool,
+    // `--print-last`: `run` prints the value of a final expression statement.
+    print_last: bool,
+    // `--max-errors N`: `tokenize` reports at most N lexical errors.
+    max_errors: Option<usize>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--pretty" => options.pretty = true,
+                "--tokens-only" => options.tokens_only = true,
+                "--lint" => options.lint = true,
+                "--warn-unused" => options.warn_unused = true,
+                "--print-last" => options.print_last = true,
+                "--max-errors" => match args.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(max)) => options.max_errors = Some(max),
+                    Some(Err(_)) | None => {
+                        return Err("Expected a number after --max-errors".to_string())
+                    }
+                },
+                "--format" => match args.next().map(String::as_str) {
+                    Some("json") => options.json = true,
+                    Some("text") => options.json = false,
+                    Some(format) => return Err(format!("Unknown format: {}", format)),
+                    None => return Err("Expected a format after --format".to_string()),
+                },
+                _ => return Err(format!("Unknown option: {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_expression(filename: &str, options: &Options) -> Result<parser::Expr, ExitCode> {
+    let mut parser = parser_for(filename, options)?;
+    parser.parse().map_err(report_parse_errors)
+}
+
+fn parse_program(filename: &str, options: &Options) -> Result<Vec<parser::Stmt>, ExitCode> {
+    let mut parser = parser_for(filename, options)?;
+    let mut statements = parser.parse_program().map_err(report_parse_errors)?;
+    let mut resolver = match options.warn_unused {
+        true => resolver::Resolver::with_unused_warnings(),
+        false => resolver::Resolver::new(),
+    };
+    let result = resolver.resolve(&mut statements);
+    for warning in resolver.warnings() {
+        eprintln!("{}", warning);
+    }
+    result.map_err(report_parse_errors)?;
+    Ok(statements)
+}
+
+fn parser_for(filename: &str, options: &Options) -> Result<parser::Parser, ExitCode> {
+    let file_contents = read_source(filename)?;
+
+    let (tokens, errors) = tokenizer::tokenize_checked(&file_contents);
+    if !errors.is_empty() {
+        for e in errors {
+            report_tokenizer_error(&file_contents, &e, options);
+        }
+        return Err(ExitCode::from(65));
+    }
+    if options.lint {
+        report_lints(&file_contents, &tokens);
+    }
+    Ok(parser::Parser::new(tokens))
+}
+
+fn read_source(filename: &str) -> Result<String, ExitCode> {
+    fs::read_to_string(filename).map_err(|error| {
+        match error.kind() {
+            io::ErrorKind::NotFound => eprintln!("File not found: {}", filename),
+            io::ErrorKind::PermissionDenied => eprintln!("Permission denied: {}", filename),
+            _ => eprintln!("Failed to read file {}: {}", filename, error),
+        }
+        // EX_NOINPUT
+        ExitCode::from(66)
+    })
+}
+
+fn report_tokenizer_error(source: &str, e: &tokenizer::TokenizerError, options: &Options) {
+    if options.pretty {
+        eprintln!("{}", tokenizer::render_error(source, e));
+    } else {
+        eprintln!("[line {}, col {}] Error: {}", e.line, e.column, e.message);
+    }
+}
+
+// Warnings never change the exit code.
+fn report_lints(source: &str, tokens: &[tokenizer::Spanned<tokenizer::Token>]) {
+    for warning in diagnostics::lint_identifiers(source, tokens) {
+        eprintln!("{}", warning);
+    }
+}
+
+fn report_parse_errors(errors: Vec<parser::ParseError>) -> ExitCode {
+    for error in errors {
+        eprintln!("{}", error);
+    }
+    ExitCode::from(65)
+}
@@ -0,0 +1,72 @@
+use crate::tokenizer::{Span, Spanned, Token, TokenType};
+
+// Position in a token stream. The stream always ends in `EOF`, and reading
+// past the end keeps returning it, so lookahead never has to bounds-check.
+pub struct TokenCursor {
+    tokens: Vec<Spanned<Token>>,
+    current: usize,
+}
+
+impl TokenCursor {
+    pub fn new(mut tokens: Vec<Spanned<Token>>) -> Self {
+        if tokens.last().map(|spanned| &spanned.token) != Some(&Token::EOF) {
+            let line = tokens.last().map_or(1, |spanned| spanned.line);
+            let end = tokens.last().map_or(0, |spanned| spanned.span.end);
+            tokens.push(Spanned {
+                token: Token::EOF,
+                line,
+                span: Span { start: end, end },
+            });
+        }
+        Self { tokens, current: 0 }
+    }
+
+    pub fn peek(&self) -> &Token {
+        self.peek_nth(0)
+    }
+
+    // `peek_nth(0)` is the current token.
+    pub fn peek_nth(&self, n: usize) -> &Token {
+        &self.spanned_nth(n).token
+    }
+
+    pub fn spanned(&self) -> &Spanned<Token> {
+        self.spanned_nth(0)
+    }
+
+    fn spanned_nth(&self, n: usize) -> &Spanned<Token> {
+        let index = (self.current + n).min(self.tokens.len() - 1);
+        &self.tokens[index]
+    }
+
+    pub fn advance(&mut self) -> &Token {
+        let token = &self.tokens[self.current].token;
+        if *token != Token::EOF {
+            self.current += 1;
+        }
+        token
+    }
+
+    pub fn check(&self, token_type: TokenType) -> bool {
+        self.peek().token_type() == token_type
+    }
+
+    // Consumes the current token only if it has the given type.
+    pub fn match_token(&mut self, token_type: TokenType) -> bool {
+        if self.check(token_type) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    // For backtracking: `restore` rewinds to a position saved earlier.
+    pub fn save(&self) -> usize {
+        self.current
+    }
+
+    pub fn restore(&mut self, position: usize) {
+        self.current = position.min(self.tokens.len() - 1);
+    }
+}
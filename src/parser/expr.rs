@@ -0,0 +1,243 @@
+use std::fmt::Display;
+
+use crate::tokenizer::Token;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Nil,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+// Every node records the line of its principal token, for runtime errors.
+pub enum Expr {
+    Literal {
+        value: Literal,
+        line: usize,
+    },
+    Unary {
+        operator: Token,
+        right: Box<Expr>,
+        line: usize,
+    },
+    Binary {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+        line: usize,
+    },
+    Logical {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+        line: usize,
+    },
+    Grouping {
+        expression: Box<Expr>,
+        line: usize,
+    },
+    Ternary {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+        line: usize,
+    },
+    // `depth` is filled in by the resolver: how many scopes out the binding
+    // lives, or `None` for globals.
+    Variable {
+        name: String,
+        line: usize,
+        depth: Option<usize>,
+    },
+    Assign {
+        name: String,
+        value: Box<Expr>,
+        line: usize,
+        depth: Option<usize>,
+    },
+    List {
+        elements: Vec<Expr>,
+        line: usize,
+    },
+    // Keys are arbitrary expressions; they must evaluate to strings.
+    Map {
+        entries: Vec<(Expr, Expr)>,
+        line: usize,
+    },
+    Index {
+        object: Box<Expr>,
+        index: Box<Expr>,
+        line: usize,
+    },
+    IndexSet {
+        object: Box<Expr>,
+        index: Box<Expr>,
+        value: Box<Expr>,
+        line: usize,
+    },
+    Call {
+        callee: Box<Expr>,
+        arguments: Vec<Expr>,
+        line: usize,
+    },
+    Get {
+        object: Box<Expr>,
+        name: String,
+        line: usize,
+    },
+    // `object?.name`: nil when `object` is nil, which also skips the rest of
+    // the chain it starts.
+    OptionalGet {
+        object: Box<Expr>,
+        name: String,
+        line: usize,
+    },
+    Set {
+        object: Box<Expr>,
+        name: String,
+        value: Box<Expr>,
+        line: usize,
+    },
+    // `object.name op= value`, kept whole so `object` is only evaluated once.
+    CompoundSet {
+        object: Box<Expr>,
+        name: String,
+        operator: Token,
+        value: Box<Expr>,
+        line: usize,
+    },
+    This {
+        line: usize,
+        depth: Option<usize>,
+    },
+    Super {
+        method: String,
+        line: usize,
+        depth: Option<usize>,
+    },
+}
+
+impl Expr {
+    pub fn line(&self) -> usize {
+        match self {
+            Expr::Literal { line, .. }
+            | Expr::Unary { line, .. }
+            | Expr::Binary { line, .. }
+            | Expr::Logical { line, .. }
+            | Expr::Grouping { line, .. }
+            | Expr::Ternary { line, .. }
+            | Expr::Variable { line, .. }
+            | Expr::Assign { line, .. }
+            | Expr::List { line, .. }
+            | Expr::Map { line, .. }
+            | Expr::Index { line, .. }
+            | Expr::IndexSet { line, .. }
+            | Expr::Call { line, .. }
+            | Expr::Get { line, .. }
+            | Expr::OptionalGet { line, .. }
+            | Expr::Set { line, .. }
+            | Expr::CompoundSet { line, .. }
+            | Expr::This { line, .. }
+            | Expr::Super { line, .. } => *line,
+        }
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Number(n) => write!(f, "{:?}", n),
+            Literal::String(s) => write!(f, "{}", s),
+            Literal::Bool(b) => write!(f, "{}", b),
+            Literal::Nil => write!(f, "nil"),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Literal { value, .. } => write!(f, "{}", value),
+            Expr::Unary {
+                operator, right, ..
+            } => write!(f, "({} {})", operator.lexeme(), right),
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => write!(f, "({} {} {})", operator.lexeme(), left, right),
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => write!(f, "({} {} {})", operator.lexeme(), left, right),
+            Expr::Grouping { expression, .. } => write!(f, "(group {})", expression),
+            Expr::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(f, "(?: {} {} {})", condition, then_branch, else_branch),
+            Expr::Variable { name, .. } => write!(f, "{}", name),
+            Expr::Assign { name, value, .. } => write!(f, "(= {} {})", name, value),
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                write!(f, "(call {}", callee)?;
+                for argument in arguments {
+                    write!(f, " {}", argument)?;
+                }
+                write!(f, ")")
+            }
+            Expr::List { elements, .. } => {
+                write!(f, "(list")?;
+                for element in elements {
+                    write!(f, " {}", element)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Map { entries, .. } => {
+                write!(f, "(map")?;
+                for (key, value) in entries {
+                    write!(f, " {} {}", key, value)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Index { object, index, .. } => write!(f, "(index {} {})", object, index),
+            Expr::IndexSet {
+                object,
+                index,
+                value,
+                ..
+            } => write!(f, "(= (index {} {}) {})", object, index, value),
+            Expr::Get { object, name, .. } => write!(f, "(. {} {})", object, name),
+            Expr::OptionalGet { object, name, .. } => write!(f, "(?. {} {})", object, name),
+            Expr::Set {
+                object,
+                name,
+                value,
+                ..
+            } => write!(f, "(= (. {} {}) {})", object, name, value),
+            Expr::CompoundSet {
+                object,
+                name,
+                operator,
+                value,
+                ..
+            } => write!(
+                f,
+                "({}= (. {} {}) {})",
+                operator.lexeme(),
+                object,
+                name,
+                value
+            ),
+            Expr::This { .. } => write!(f, "this"),
+            Expr::Super { method, .. } => write!(f, "(super {})", method),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::tokenizer::json_string;
+
+use super::{Expr, FunctionDecl, Literal, Stmt};
+
+// Renders a program as a JSON array of statements, in the same shape as
+// `to_json` uses for expressions.
+pub fn program_to_json(statements: &[Stmt]) -> String {
+    array(statements.iter().map(stmt_json))
+}
+
+fn stmt_json(stmt: &Stmt) -> String {
+    match stmt {
+        Stmt::Expression(expr) => {
+            object("Expression", expr.line(), &[("expression", to_json(expr))])
+        }
+        Stmt::Print { values, line } => object(
+            "Print",
+            *line,
+            &[("values", array(values.iter().map(to_json)))],
+        ),
+        Stmt::Block { statements, line } => object(
+            "Block",
+            *line,
+            &[("statements", program_to_json(statements))],
+        ),
+        Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+            line,
+        } => object(
+            "If",
+            *line,
+            &[
+                ("condition", to_json(condition)),
+                ("then", stmt_json(then_branch)),
+                ("else", optional(else_branch.as_deref().map(stmt_json))),
+            ],
+        ),
+        Stmt::While {
+            condition,
+            body,
+            increment,
+            line,
+        } => object(
+            "While",
+            *line,
+            &[
+                ("condition", to_json(condition)),
+                ("body", stmt_json(body)),
+                ("increment", optional(increment.as_ref().map(to_json))),
+            ],
+        ),
+        Stmt::Var {
+            name,
+            initializer,
+            line,
+        } => object(
+            "Var",
+            *line,
+            &[
+                ("name", json_string(name)),
+                ("initializer", optional(initializer.as_ref().map(to_json))),
+            ],
+        ),
+        Stmt::Function(declaration) => function_json(declaration),
+        Stmt::Class {
+            name,
+            superclass,
+            methods,
+            static_methods,
+            line,
+        } => object(
+            "Class",
+            *line,
+            &[
+                ("name", json_string(name)),
+                ("superclass", optional(superclass.as_ref().map(to_json))),
+                ("methods", array(methods.iter().map(function_json))),
+                (
+                    "static_methods",
+                    array(static_methods.iter().map(function_json)),
+                ),
+            ],
+        ),
+        Stmt::Return { value, line } => object(
+            "Return",
+            *line,
+            &[("value", optional(value.as_ref().map(to_json)))],
+        ),
+        Stmt::Break { line } => object("Break", *line, &[]),
+        Stmt::Continue { line } => object("Continue", *line, &[]),
+    }
+}
+
+fn function_json(declaration: &FunctionDecl) -> String {
+    object(
+        "Function",
+        declaration.line,
+        &[
+            ("name", json_string(&declaration.name)),
+            (
+                "params",
+                array(declaration.params.iter().map(|param| json_string(param))),
+            ),
+            ("getter", declaration.getter.to_string()),
+            ("body", program_to_json(&declaration.body)),
+        ],
+    )
+}
+
+// Renders an expression tree as JSON. Every node carries a "type" tag and
+// its source line.
+pub fn to_json(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal { value, line } => {
+            object("Literal", *line, &[("value", literal_json(value))])
+        }
+        Expr::Unary {
+            operator,
+            right,
+            line,
+        } => object(
+            "Unary",
+            *line,
+            &[
+                ("operator", json_string(&operator.lexeme())),
+                ("right", to_json(right)),
+            ],
+        ),
+        Expr::Binary {
+            left,
+            operator,
+            right,
+            line,
+        } => object(
+            "Binary",
+            *line,
+            &[
+                ("operator", json_string(&operator.lexeme())),
+                ("left", to_json(left)),
+                ("right", to_json(right)),
+            ],
+        ),
+        Expr::Logical {
+            left,
+            operator,
+            right,
+            line,
+        } => object(
+            "Logical",
+            *line,
+            &[
+                ("operator", json_string(&operator.lexeme())),
+                ("left", to_json(left)),
+                ("right", to_json(right)),
+            ],
+        ),
+        Expr::Grouping { expression, line } => {
+            object("Grouping", *line, &[("expression", to_json(expression))])
+        }
+        Expr::Ternary {
+            condition,
+            then_branch,
+            else_branch,
+            line,
+        } => object(
+            "Ternary",
+            *line,
+            &[
+                ("condition", to_json(condition)),
+                ("then", to_json(then_branch)),
+                ("else", to_json(else_branch)),
+            ],
+        ),
+        Expr::Variable { name, line, .. } => {
+            object("Variable", *line, &[("name", json_string(name))])
+        }
+        Expr::Assign {
+            name, value, line, ..
+        } => object(
+            "Assign",
+            *line,
+            &[("name", json_string(name)), ("value", to_json(value))],
+        ),
+        Expr::Call {
+            callee,
+            arguments,
+            line,
+        } => object(
+            "Call",
+            *line,
+            &[
+                ("callee", to_json(callee)),
+                ("arguments", array(arguments.iter().map(to_json))),
+            ],
+        ),
+        Expr::List { elements, line } => object(
+            "List",
+            *line,
+            &[("elements", array(elements.iter().map(to_json)))],
+        ),
+        Expr::Map { entries, line } => {
+            let entries = entries.iter().map(|(key, value)| {
+                format!(
+                    "{{\"key\": {}, \"value\": {}}}",
+                    to_json(key),
+                    to_json(value)
+                )
+            });
+            object("Map", *line, &[("entries", array(entries))])
+        }
+        Expr::Index {
+            object: target,
+            index,
+            line,
+        } => object(
+            "Index",
+            *line,
+            &[("object", to_json(target)), ("index", to_json(index))],
+        ),
+        Expr::IndexSet {
+            object: target,
+            index,
+            value,
+            line,
+        } => object(
+            "IndexSet",
+            *line,
+            &[
+                ("object", to_json(target)),
+                ("index", to_json(index)),
+                ("value", to_json(value)),
+            ],
+        ),
+        Expr::Get {
+            object: target,
+            name,
+            line,
+        } => object(
+            "Get",
+            *line,
+            &[("object", to_json(target)), ("name", json_string(name))],
+        ),
+        Expr::OptionalGet {
+            object: target,
+            name,
+            line,
+        } => object(
+            "OptionalGet",
+            *line,
+            &[("object", to_json(target)), ("name", json_string(name))],
+        ),
+        Expr::Set {
+            object: target,
+            name,
+            value,
+            line,
+        } => object(
+            "Set",
+            *line,
+            &[
+                ("object", to_json(target)),
+                ("name", json_string(name)),
+                ("value", to_json(value)),
+            ],
+        ),
+        Expr::CompoundSet {
+            object: target,
+            name,
+            operator,
+            value,
+            line,
+        } => object(
+            "CompoundSet",
+            *line,
+            &[
+                ("object", to_json(target)),
+                ("name", json_string(name)),
+                ("operator", json_string(&operator.lexeme())),
+                ("value", to_json(value)),
+            ],
+        ),
+        Expr::This { line, .. } => object("This", *line, &[]),
+        Expr::Super { method, line, .. } => {
+            object("Super", *line, &[("method", json_string(method))])
+        }
+    }
+}
+
+fn object(kind: &str, line: usize, fields: &[(&str, String)]) -> String {
+    let mut members = vec![
+        format!("\"type\": {}", json_string(kind)),
+        format!("\"line\": {}", line),
+    ];
+    for (key, value) in fields {
+        members.push(format!("{}: {}", json_string(key), value));
+    }
+    format!("{{{}}}", members.join(", "))
+}
+
+fn array(items: impl Iterator<Item = String>) -> String {
+    format!("[{}]", items.collect::<Vec<_>>().join(", "))
+}
+
+fn optional(json: Option<String>) -> String {
+    json.unwrap_or_else(|| "null".to_string())
+}
+
+fn literal_json(literal: &Literal) -> String {
+    match literal {
+        Literal::Number(n) if n.is_finite() => format!("{:?}", n),
+        Literal::Number(_) | Literal::Nil => "null".to_string(),
+        Literal::String(s) => json_string(s),
+        Literal::Bool(b) => b.to_string(),
+    }
+}
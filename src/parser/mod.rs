@@ -0,0 +1,851 @@
+use std::fmt::Display;
+
+use crate::tokenizer::{Spanned, Token};
+
+pub use cursor::TokenCursor;
+pub use expr::{Expr, Literal};
+pub use json::{program_to_json, to_json};
+pub use printer::{AstPrinter, PrintStyle};
+pub use stmt::{FunctionDecl, Stmt};
+
+mod cursor;
+mod expr;
+mod json;
+mod printer;
+mod stmt;
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[line {}] {}", self.line, self.message)
+    }
+}
+
+// How deeply expressions may nest before parsing gives up, so pathological
+// input is reported instead of overflowing the stack.
+pub const MAX_DEPTH: usize = 250;
+
+// Calls and declarations are capped like in reference Lox. Going over is
+// reported but doesn't stop parsing.
+const MAX_ARGUMENTS: usize = 255;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassKind {
+    None,
+    Class,
+    Subclass,
+    // Static methods are never bound, so neither `this` nor `super` exists.
+    StaticMethod,
+}
+
+pub struct Parser {
+    tokens: TokenCursor,
+    class_kind: ClassKind,
+    // Loops enclosing the current statement within the current function.
+    loop_depth: usize,
+    // Expression rules currently being parsed, bounded by `max_depth`.
+    depth: usize,
+    max_depth: usize,
+    // Errors that don't stop the current rule; fatal ones unwind via `Result`.
+    errors: Vec<ParseError>,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Spanned<Token>>) -> Self {
+        let tokens = tokens
+            .into_iter()
+            .filter(|spanned| {
+                !matches!(
+                    spanned.token,
+                    Token::LineComment(_) | Token::BlockComment(_)
+                )
+            })
+            .collect();
+        Self {
+            tokens: TokenCursor::new(tokens),
+            class_kind: ClassKind::None,
+            loop_depth: 0,
+            depth: 0,
+            max_depth: MAX_DEPTH,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn with_max_depth(tokens: Vec<Spanned<Token>>, max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            ..Self::new(tokens)
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Expr, Vec<ParseError>> {
+        loop {
+            // The expression has to be the whole input: `1 2` is an error.
+            let expr = self.expression().and_then(|expr| {
+                self.consume(Token::EOF, "Expect end of expression.")?;
+                Ok(expr)
+            });
+            match expr {
+                Ok(expr) if self.errors.is_empty() => return Ok(expr),
+                Ok(_) => self.synchronize(),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                }
+            }
+            if *self.peek() == Token::EOF {
+                return Err(std::mem::take(&mut self.errors));
+            }
+        }
+    }
+
+    pub fn parse_program(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
+        let mut statements = Vec::new();
+        while *self.peek() != Token::EOF {
+            match self.declaration() {
+                Ok(stmt) => statements.push(stmt),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                }
+            }
+        }
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        if *self.peek() == Token::Var {
+            self.advance();
+            return self.var_declaration();
+        }
+        if *self.peek() == Token::Fun {
+            self.advance();
+            return Ok(Stmt::Function(self.function("function")?));
+        }
+        if *self.peek() == Token::Class {
+            self.advance();
+            return self.class_declaration();
+        }
+        self.statement()
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let line = self.line();
+        let name = self.consume_identifier("Expect class name.")?;
+        let mut superclass = None;
+        if *self.peek() == Token::Less {
+            self.advance();
+            if matches!(self.peek(), Token::Identifier(superclass) if **superclass == *name) {
+                return Err(self.error("A class can't inherit from itself."));
+            }
+            let line = self.line();
+            let name = self.consume_identifier("Expect superclass name.")?;
+            superclass = Some(Expr::Variable {
+                name,
+                line,
+                depth: None,
+            });
+        }
+        self.consume(Token::LeftBrace, "Expect '{' before class body.")?;
+
+        let kind = match superclass {
+            Some(_) => ClassKind::Subclass,
+            None => ClassKind::Class,
+        };
+        let enclosing = std::mem::replace(&mut self.class_kind, kind);
+        let body = self.class_body();
+        self.class_kind = enclosing;
+        let (methods, static_methods) = body?;
+
+        Ok(Stmt::Class {
+            name,
+            superclass,
+            methods,
+            static_methods,
+            line,
+        })
+    }
+
+    fn class_body(&mut self) -> Result<(Vec<FunctionDecl>, Vec<FunctionDecl>), ParseError> {
+        let mut methods = Vec::new();
+        let mut static_methods = Vec::new();
+        while !matches!(self.peek(), Token::RightBrace | Token::EOF) {
+            // `static` is contextual, so a method may still be named `static`.
+            let is_static = matches!(self.peek(), Token::Identifier(word) if &**word == "static")
+                && matches!(self.tokens.peek_nth(1), Token::Identifier(_));
+            if is_static {
+                self.advance();
+                let enclosing = std::mem::replace(&mut self.class_kind, ClassKind::StaticMethod);
+                let method = self.function("method");
+                self.class_kind = enclosing;
+                static_methods.push(method?);
+            } else {
+                methods.push(self.function("method")?);
+            }
+        }
+        self.consume(Token::RightBrace, "Expect '}' after class body.")?;
+        Ok((methods, static_methods))
+    }
+
+    fn function(&mut self, kind: &str) -> Result<FunctionDecl, ParseError> {
+        let line = self.line();
+        let name = self.consume_identifier(&format!("Expect {} name.", kind))?;
+        let getter = kind == "method" && *self.peek() == Token::LeftBrace;
+        let mut params = Vec::new();
+        if !getter {
+            self.consume(
+                Token::LeftParen,
+                &format!("Expect '(' after {} name.", kind),
+            )?;
+            if *self.peek() != Token::RightParen {
+                loop {
+                    if params.len() == MAX_ARGUMENTS {
+                        let error = self.error("Can't have more than 255 parameters.");
+                        self.errors.push(error);
+                    }
+                    params.push(self.consume_identifier("Expect parameter name.")?);
+                    if *self.peek() != Token::Comma {
+                        break;
+                    }
+                    self.advance();
+                }
+            }
+            self.consume(Token::RightParen, "Expect ')' after parameters.")?;
+        }
+        self.consume(
+            Token::LeftBrace,
+            &format!("Expect '{{' before {} body.", kind),
+        )?;
+        // A function body starts outside of any loop, even if declared in one.
+        let enclosing = std::mem::replace(&mut self.loop_depth, 0);
+        let body = self.block();
+        self.loop_depth = enclosing;
+        let body = body?;
+        Ok(FunctionDecl {
+            name,
+            params,
+            body,
+            line,
+            getter,
+        })
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let line = self.line();
+        let name = self.consume_identifier("Expect variable name.")?;
+        let mut initializer = None;
+        if *self.peek() == Token::Equal {
+            self.advance();
+            initializer = Some(self.expression()?);
+        }
+        self.consume(Token::Semicolon, "Expect ';' after variable declaration.")?;
+        Ok(Stmt::Var {
+            name,
+            initializer,
+            line,
+        })
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ParseError> {
+        let line = self.line();
+        if *self.peek() == Token::Print {
+            self.advance();
+            let mut values = vec![self.expression()?];
+            while *self.peek() == Token::Comma {
+                self.advance();
+                values.push(self.expression()?);
+            }
+            self.consume(Token::Semicolon, "Expect ';' after value.")?;
+            return Ok(Stmt::Print { values, line });
+        }
+        if *self.peek() == Token::LeftBrace {
+            self.advance();
+            let statements = self.block()?;
+            return Ok(Stmt::Block { statements, line });
+        }
+        if *self.peek() == Token::If {
+            self.advance();
+            return self.if_statement(line);
+        }
+        if *self.peek() == Token::While {
+            self.advance();
+            return self.while_statement(line);
+        }
+        if *self.peek() == Token::For {
+            self.advance();
+            return self.for_statement(line);
+        }
+        if *self.peek() == Token::Return {
+            self.advance();
+            let mut value = None;
+            if *self.peek() != Token::Semicolon {
+                value = Some(self.expression()?);
+            }
+            self.consume(Token::Semicolon, "Expect ';' after return value.")?;
+            return Ok(Stmt::Return { value, line });
+        }
+        if matches!(self.peek(), Token::Break | Token::Continue) {
+            let keyword = self.peek().clone();
+            if self.loop_depth == 0 {
+                let message = format!("Can't use '{}' outside of a loop.", keyword.lexeme());
+                let error = self.error(&message);
+                self.errors.push(error);
+            }
+            self.advance();
+            self.consume(
+                Token::Semicolon,
+                &format!("Expect ';' after '{}'.", keyword.lexeme()),
+            )?;
+            return Ok(match keyword {
+                Token::Break => Stmt::Break { line },
+                _ => Stmt::Continue { line },
+            });
+        }
+        let expr = self.expression()?;
+        self.consume(Token::Semicolon, "Expect ';' after expression.")?;
+        Ok(Stmt::Expression(expr))
+    }
+
+    fn if_statement(&mut self, line: usize) -> Result<Stmt, ParseError> {
+        self.consume(Token::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(Token::RightParen, "Expect ')' after if condition.")?;
+        let then_branch = Box::new(self.statement()?);
+        // Binding `else` eagerly attaches it to the nearest `if`.
+        let mut else_branch = None;
+        if *self.peek() == Token::Else {
+            self.advance();
+            else_branch = Some(Box::new(self.statement()?));
+        }
+        Ok(Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+            line,
+        })
+    }
+
+    fn while_statement(&mut self, line: usize) -> Result<Stmt, ParseError> {
+        self.consume(Token::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(Token::RightParen, "Expect ')' after condition.")?;
+        let body = Box::new(self.loop_body()?);
+        Ok(Stmt::While {
+            condition,
+            body,
+            increment: None,
+            line,
+        })
+    }
+
+    fn loop_body(&mut self) -> Result<Stmt, ParseError> {
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+        body
+    }
+
+    // There is no dedicated for-node: the loop is desugared into an optional
+    // initializer followed by a while loop that runs the increment last.
+    fn for_statement(&mut self, line: usize) -> Result<Stmt, ParseError> {
+        self.consume(Token::LeftParen, "Expect '(' after 'for'.")?;
+        let initializer = match self.peek() {
+            Token::Semicolon => {
+                self.advance();
+                None
+            }
+            Token::Var => {
+                self.advance();
+                Some(self.var_declaration()?)
+            }
+            _ => {
+                let expr = self.expression()?;
+                self.consume(Token::Semicolon, "Expect ';' after expression.")?;
+                Some(Stmt::Expression(expr))
+            }
+        };
+
+        let condition = match self.peek() {
+            Token::Semicolon => Expr::Literal {
+                value: Literal::Bool(true),
+                line,
+            },
+            _ => self.expression()?,
+        };
+        self.consume(Token::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = match self.peek() {
+            Token::RightParen => None,
+            _ => Some(self.expression()?),
+        };
+        self.consume(Token::RightParen, "Expect ')' after for clauses.")?;
+
+        let mut body = Stmt::While {
+            condition,
+            body: Box::new(self.loop_body()?),
+            increment,
+            line,
+        };
+        if let Some(initializer) = initializer {
+            body = Stmt::Block {
+                statements: vec![initializer, body],
+                line,
+            };
+        }
+        Ok(body)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements = Vec::new();
+        while !matches!(self.peek(), Token::RightBrace | Token::EOF) {
+            statements.push(self.declaration()?);
+        }
+        self.consume(Token::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn expression(&mut self) -> Result<Expr, ParseError> {
+        self.nested(Self::assignment)
+    }
+
+    // Every rule that can recurse into itself goes through here.
+    fn nested(
+        &mut self,
+        rule: fn(&mut Self) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        if self.depth >= self.max_depth {
+            return Err(self.error("Too many nested expressions."));
+        }
+        self.depth += 1;
+        let result = rule(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn assignment(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.ternary()?;
+        // Compound operators carry the binary operator they apply.
+        let operator = match self.peek() {
+            Token::Equal => None,
+            Token::PlusEqual => Some(Token::Plus),
+            Token::MinusEqual => Some(Token::Minus),
+            Token::StarEqual => Some(Token::Star),
+            Token::SlashEqual => Some(Token::Slash),
+            _ => return Ok(expr),
+        };
+        let error = self.error("Invalid assignment target.");
+        let line = self.line();
+        self.advance();
+        let value = self.nested(Self::assignment)?;
+        match (expr, operator) {
+            (
+                Expr::Variable {
+                    name,
+                    line: target_line,
+                    ..
+                },
+                operator,
+            ) => {
+                // `a op= b` desugars to `a = a op b`; reading a variable has no side effects.
+                let value = match operator {
+                    Some(operator) => Expr::Binary {
+                        left: Box::new(Expr::Variable {
+                            name: name.clone(),
+                            line: target_line,
+                            depth: None,
+                        }),
+                        operator,
+                        right: Box::new(value),
+                        line,
+                    },
+                    None => value,
+                };
+                Ok(Expr::Assign {
+                    name,
+                    value: Box::new(value),
+                    line,
+                    depth: None,
+                })
+            }
+            (Expr::Index { object, index, .. }, None) => Ok(Expr::IndexSet {
+                object,
+                index,
+                value: Box::new(value),
+                line,
+            }),
+            (Expr::Get { object, name, .. }, None) => Ok(Expr::Set {
+                object,
+                name,
+                value: Box::new(value),
+                line,
+            }),
+            (Expr::Get { object, name, .. }, Some(operator)) => Ok(Expr::CompoundSet {
+                object,
+                name,
+                operator,
+                value: Box::new(value),
+                line,
+            }),
+            // The parser isn't confused by a bad target, so parsing goes on.
+            (expr, _) => {
+                self.errors.push(error);
+                Ok(expr)
+            }
+        }
+    }
+
+    // Right-associative: `a ? b : c ? d : e` groups as `a ? b : (c ? d : e)`.
+    fn ternary(&mut self) -> Result<Expr, ParseError> {
+        let condition = self.or()?;
+        if *self.peek() != Token::Question {
+            return Ok(condition);
+        }
+        let line = self.line();
+        self.advance();
+        let then_branch = self.expression()?;
+        self.consume(
+            Token::Colon,
+            "Expect ':' after then branch of conditional expression.",
+        )?;
+        let else_branch = self.nested(Self::ternary)?;
+        Ok(Expr::Ternary {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+            line,
+        })
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        self.logical(&[Token::Or, Token::PipePipe], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        self.logical(&[Token::And, Token::AmpAmp], Self::equality)
+    }
+
+    // Like `binary`, but builds nodes the interpreter short-circuits.
+    fn logical(
+        &mut self,
+        operators: &[Token],
+        operand: fn(&mut Self) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        let mut expr = operand(self)?;
+        while operators.contains(self.peek()) {
+            let line = self.line();
+            let operator = self.advance().clone();
+            let right = operand(self)?;
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+                line,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<Expr, ParseError> {
+        self.binary(&[Token::BangEqual, Token::EqualEqual], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            &[
+                Token::Greater,
+                Token::GreaterEqual,
+                Token::Less,
+                Token::LessEqual,
+            ],
+            Self::term,
+        )
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        self.binary(&[Token::Minus, Token::Plus], Self::factor)
+    }
+
+    fn factor(&mut self) -> Result<Expr, ParseError> {
+        self.binary(&[Token::Slash, Token::Star, Token::Percent], Self::unary)
+    }
+
+    // Left-associative binary level: operands come from the next-higher precedence rule.
+    fn binary(
+        &mut self,
+        operators: &[Token],
+        operand: fn(&mut Self) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        let mut expr = operand(self)?;
+        while operators.contains(self.peek()) {
+            let line = self.line();
+            let operator = self.advance().clone();
+            let right = operand(self)?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+                line,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if matches!(self.peek(), Token::Bang | Token::Minus) {
+            let line = self.line();
+            let operator = self.advance().clone();
+            let right = self.nested(Self::unary)?;
+            return Ok(Expr::Unary {
+                operator,
+                right: Box::new(right),
+                line,
+            });
+        }
+        self.call()
+    }
+
+    fn call(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.primary()?;
+        loop {
+            if *self.peek() == Token::Dot {
+                let line = self.line();
+                self.advance();
+                let name = self.consume_identifier("Expect property name after '.'.")?;
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                    line,
+                };
+                continue;
+            }
+            if *self.peek() == Token::QuestionDot {
+                let line = self.line();
+                self.advance();
+                let name = self.consume_identifier("Expect property name after '?.'.")?;
+                expr = Expr::OptionalGet {
+                    object: Box::new(expr),
+                    name,
+                    line,
+                };
+                continue;
+            }
+            if *self.peek() == Token::LeftBracket {
+                let line = self.line();
+                self.advance();
+                let index = self.expression()?;
+                self.consume(Token::RightBracket, "Expect ']' after index.")?;
+                expr = Expr::Index {
+                    object: Box::new(expr),
+                    index: Box::new(index),
+                    line,
+                };
+                continue;
+            }
+            if *self.peek() != Token::LeftParen {
+                break;
+            }
+            self.advance();
+            let mut arguments = Vec::new();
+            if *self.peek() != Token::RightParen {
+                loop {
+                    if arguments.len() == MAX_ARGUMENTS {
+                        let error = self.error("Can't have more than 255 arguments.");
+                        self.errors.push(error);
+                    }
+                    arguments.push(self.expression()?);
+                    if *self.peek() != Token::Comma {
+                        break;
+                    }
+                    self.advance();
+                }
+            }
+            let line = self.line();
+            self.consume(Token::RightParen, "Expect ')' after arguments.")?;
+            expr = Expr::Call {
+                callee: Box::new(expr),
+                arguments,
+                line,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let line = self.line();
+        let literal = |value| Expr::Literal { value, line };
+        let expr = match self.peek().clone() {
+            Token::True => literal(Literal::Bool(true)),
+            Token::False => literal(Literal::Bool(false)),
+            Token::Nil => literal(Literal::Nil),
+            Token::Number { value, .. } => literal(Literal::Number(value)),
+            Token::String(s) => literal(Literal::String(s)),
+            // Chars evaluate as one-character strings.
+            Token::Char(c) => literal(Literal::String(c.to_string())),
+            Token::Identifier(name) => Expr::Variable {
+                name: name.to_string(),
+                line: self.line(),
+                depth: None,
+            },
+            Token::This => {
+                let misuse = match self.class_kind {
+                    ClassKind::None => Some("Can't use 'this' outside of a class."),
+                    ClassKind::Class | ClassKind::Subclass => None,
+                    ClassKind::StaticMethod => Some("Can't use 'this' in a static method."),
+                };
+                if let Some(message) = misuse {
+                    let error = self.error(message);
+                    self.errors.push(error);
+                }
+                Expr::This {
+                    line: self.line(),
+                    depth: None,
+                }
+            }
+            Token::Super => {
+                let misuse = match self.class_kind {
+                    ClassKind::None => Some("Can't use 'super' outside of a class."),
+                    ClassKind::Class => Some("Can't use 'super' in a class with no superclass."),
+                    ClassKind::Subclass => None,
+                    ClassKind::StaticMethod => Some("Can't use 'super' in a static method."),
+                };
+                if let Some(message) = misuse {
+                    let error = self.error(message);
+                    self.errors.push(error);
+                }
+                let line = self.line();
+                self.advance();
+                self.consume(Token::Dot, "Expect '.' after 'super'.")?;
+                let method = self.consume_identifier("Expect superclass method name.")?;
+                return Ok(Expr::Super {
+                    method,
+                    line,
+                    depth: None,
+                });
+            }
+            Token::LeftBracket => {
+                let line = self.line();
+                self.advance();
+                let mut elements = Vec::new();
+                if *self.peek() != Token::RightBracket {
+                    loop {
+                        elements.push(self.expression()?);
+                        if *self.peek() != Token::Comma {
+                            break;
+                        }
+                        self.advance();
+                    }
+                }
+                self.consume(Token::RightBracket, "Expect ']' after list elements.")?;
+                return Ok(Expr::List { elements, line });
+            }
+            // Blocks are statements, so in expression position a `{` can only
+            // open a map. A map used as an expression statement needs
+            // parentheses, as `{` at the start of a statement is a block.
+            Token::LeftBrace => {
+                let line = self.line();
+                self.advance();
+                let mut entries = Vec::new();
+                if *self.peek() != Token::RightBrace {
+                    loop {
+                        let key = self.expression()?;
+                        self.consume(Token::Colon, "Expect ':' after map key.")?;
+                        entries.push((key, self.expression()?));
+                        if *self.peek() != Token::Comma {
+                            break;
+                        }
+                        self.advance();
+                    }
+                }
+                self.consume(Token::RightBrace, "Expect '}' after map entries.")?;
+                return Ok(Expr::Map { entries, line });
+            }
+            Token::LeftParen => {
+                self.advance();
+                let expr = self.expression()?;
+                self.consume(Token::RightParen, "Expect ')' after expression.")?;
+                return Ok(Expr::Grouping {
+                    expression: Box::new(expr),
+                    line,
+                });
+            }
+            _ => return Err(self.error("Expect expression.")),
+        };
+        self.advance();
+        Ok(expr)
+    }
+
+    fn consume(&mut self, expected: Token, message: &str) -> Result<&Token, ParseError> {
+        if *self.peek() == expected {
+            return Ok(self.advance());
+        }
+        Err(self.error(message))
+    }
+
+    fn consume_identifier(&mut self, message: &str) -> Result<String, ParseError> {
+        match self.peek().clone() {
+            Token::Identifier(name) => {
+                self.advance();
+                Ok(name.to_string())
+            }
+            _ => Err(self.error(message)),
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let spanned = self.tokens.spanned();
+        let location = match &spanned.token {
+            Token::EOF => "end".to_string(),
+            token => format!("'{}'", token.lexeme()),
+        };
+        ParseError {
+            line: spanned.line,
+            message: format!("Error at {}: {}", location, message),
+        }
+    }
+
+    // Discards tokens until a likely statement boundary so parsing can resume
+    // and report further errors instead of cascading from the first one.
+    fn synchronize(&mut self) {
+        while *self.peek() != Token::EOF {
+            if *self.advance() == Token::Semicolon {
+                return;
+            }
+            if matches!(
+                self.peek(),
+                Token::Class
+                    | Token::Fun
+                    | Token::Var
+                    | Token::For
+                    | Token::If
+                    | Token::While
+                    | Token::Print
+                    | Token::Return
+            ) {
+                return;
+            }
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        self.tokens.peek()
+    }
+
+    fn line(&self) -> usize {
+        self.tokens.spanned().line
+    }
+
+    fn advance(&mut self) -> &Token {
+        self.tokens.advance()
+    }
+}
@@ -0,0 +1,142 @@
+use super::Expr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrintStyle {
+    // `(+ 1 (* 2 3))`, the same as `Expr`'s `Display`.
+    Lisp,
+    // `1 2 3 * +`: operands first, so grouping never needs parentheses.
+    ReversePolish,
+}
+
+pub struct AstPrinter {
+    style: PrintStyle,
+}
+
+impl AstPrinter {
+    pub fn new(style: PrintStyle) -> Self {
+        Self { style }
+    }
+
+    pub fn print(&self, expr: &Expr) -> String {
+        match self.style {
+            PrintStyle::Lisp => expr.to_string(),
+            PrintStyle::ReversePolish => reverse_polish(expr),
+        }
+    }
+}
+
+fn reverse_polish(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal { value, .. } => value.to_string(),
+        Expr::Unary {
+            operator, right, ..
+        } => format!("{} {}", reverse_polish(right), operator.lexeme()),
+        Expr::Binary {
+            left,
+            operator,
+            right,
+            ..
+        }
+        | Expr::Logical {
+            left,
+            operator,
+            right,
+            ..
+        } => format!(
+            "{} {} {}",
+            reverse_polish(left),
+            reverse_polish(right),
+            operator.lexeme()
+        ),
+        Expr::Grouping { expression, .. } => reverse_polish(expression),
+        Expr::Ternary {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => format!(
+            "{} {} {} ?:",
+            reverse_polish(condition),
+            reverse_polish(then_branch),
+            reverse_polish(else_branch)
+        ),
+        Expr::Variable { name, .. } => name.clone(),
+        Expr::Assign { name, value, .. } => format!("{} {} =", name, reverse_polish(value)),
+        Expr::Call {
+            callee, arguments, ..
+        } => {
+            let mut output = reverse_polish(callee);
+            for argument in arguments {
+                output.push(' ');
+                output.push_str(&reverse_polish(argument));
+            }
+            output.push_str(" call");
+            output
+        }
+        Expr::List { elements, .. } => {
+            let mut output = String::new();
+            for element in elements {
+                output.push_str(&reverse_polish(element));
+                output.push(' ');
+            }
+            output.push_str(&format!("list/{}", elements.len()));
+            output
+        }
+        Expr::Map { entries, .. } => {
+            let mut output = String::new();
+            for (key, value) in entries {
+                output.push_str(&format!(
+                    "{} {} ",
+                    reverse_polish(key),
+                    reverse_polish(value)
+                ));
+            }
+            output.push_str(&format!("map/{}", entries.len()));
+            output
+        }
+        Expr::Index { object, index, .. } => {
+            format!("{} {} []", reverse_polish(object), reverse_polish(index))
+        }
+        Expr::IndexSet {
+            object,
+            index,
+            value,
+            ..
+        } => format!(
+            "{} {} [] {} =",
+            reverse_polish(object),
+            reverse_polish(index),
+            reverse_polish(value)
+        ),
+        Expr::Get { object, name, .. } => format!("{} {} .", reverse_polish(object), name),
+        Expr::OptionalGet { object, name, .. } => {
+            format!("{} {} ?.", reverse_polish(object), name)
+        }
+        Expr::Set {
+            object,
+            name,
+            value,
+            ..
+        } => format!(
+            "{} {} . {} =",
+            reverse_polish(object),
+            name,
+            reverse_polish(value)
+        ),
+        Expr::CompoundSet {
+            object,
+            name,
+            operator,
+            value,
+            ..
+        } => format!(
+            "{} {} . {} {}=",
+            reverse_polish(object),
+            name,
+            reverse_polish(value),
+            operator.lexeme()
+        ),
+        Expr::This { .. } => "this".to_string(),
+        Expr::Super { method, .. } => format!("super {} .", method),
+    }
+}
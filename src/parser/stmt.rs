@@ -0,0 +1,82 @@
+use super::Expr;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionDecl {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<Stmt>,
+    pub line: usize,
+    // Methods declared without a parameter list run on property access.
+    pub getter: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+// Like `Expr`, every statement knows the line it starts on; an expression
+// statement shares its expression's.
+pub enum Stmt {
+    Expression(Expr),
+    // `print a, b;` prints its values on one line, separated by spaces.
+    Print {
+        values: Vec<Expr>,
+        line: usize,
+    },
+    Block {
+        statements: Vec<Stmt>,
+        line: usize,
+    },
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+        line: usize,
+    },
+    // `increment` is only set by desugared `for` loops; it runs after the
+    // body even when the body `continue`s.
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+        increment: Option<Expr>,
+        line: usize,
+    },
+    Var {
+        name: String,
+        initializer: Option<Expr>,
+        line: usize,
+    },
+    Function(FunctionDecl),
+    Class {
+        name: String,
+        superclass: Option<Expr>,
+        methods: Vec<FunctionDecl>,
+        static_methods: Vec<FunctionDecl>,
+        line: usize,
+    },
+    Return {
+        value: Option<Expr>,
+        line: usize,
+    },
+    Break {
+        line: usize,
+    },
+    Continue {
+        line: usize,
+    },
+}
+
+impl Stmt {
+    pub fn line(&self) -> usize {
+        match self {
+            Stmt::Expression(expr) => expr.line(),
+            Stmt::Function(declaration) => declaration.line,
+            Stmt::Print { line, .. }
+            | Stmt::Block { line, .. }
+            | Stmt::If { line, .. }
+            | Stmt::While { line, .. }
+            | Stmt::Var { line, .. }
+            | Stmt::Class { line, .. }
+            | Stmt::Return { line, .. }
+            | Stmt::Break { line }
+            | Stmt::Continue { line } => *line,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use super::*;
+use crate::tokenizer::{tokenize_checked, TokenType};
+
+fn parse(source: &str) -> Result<Expr, Vec<ParseError>> {
+    Parser::new(tokenize_checked(source).0).parse()
+}
+
+fn parse_program(source: &str) -> Result<Vec<Stmt>, Vec<ParseError>> {
+    Parser::new(tokenize_checked(source).0).parse_program()
+}
+
+// Rendered errors, for sources that should fail to parse.
+fn program_errors(source: &str) -> Vec<String> {
+    match parse_program(source) {
+        Ok(_) => panic!("expected parse errors"),
+        Err(errors) => errors.iter().map(ParseError::to_string).collect(),
+    }
+}
+
+// The expression's S-expression form, as printed by the `parse` command.
+fn printed(source: &str) -> String {
+    parse(source).expect("source should parse").to_string()
+}
+
+#[test]
+fn prints_literals_and_groupings() {
+    assert_eq!(printed("1 + 2"), "(+ 1.0 2.0)");
+    assert_eq!(printed("\"hi\""), "hi");
+    assert_eq!(printed("true"), "true");
+    assert_eq!(printed("nil"), "nil");
+    assert_eq!(printed("(1)"), "(group 1.0)");
+    assert_eq!(printed("-x"), "(- x)");
+}
+
+#[test]
+fn precedence_shapes_the_tree() {
+    assert_eq!(
+        printed("1 + 2 * 3 - 4 / 2"),
+        "(- (+ 1.0 (* 2.0 3.0)) (/ 4.0 2.0))"
+    );
+    assert_eq!(printed("!a == b < c"), "(== (! a) (< b c))");
+    assert_eq!(printed("(1 + 2) * 3"), "(* (group (+ 1.0 2.0)) 3.0)");
+    assert_eq!(printed("a or b and c"), "(or a (and b c))");
+}
+
+#[test]
+fn unclosed_group_is_reported() {
+    let errors = parse("(1 + 2").unwrap_err();
+    assert_eq!(
+        errors,
+        [ParseError {
+            line: 1,
+            message: "Error at end: Expect ')' after expression.".to_string()
+        }]
+    );
+}
+
+#[test]
+fn dangling_operator_is_reported() {
+    let errors = parse("1 +").unwrap_err();
+    assert_eq!(errors[0].message, "Error at end: Expect expression.");
+}
+
+#[test]
+fn parsing_resumes_after_an_error() {
+    assert_eq!(
+        program_errors("print (1;\nprint 2;\nprint 3 +;"),
+        [
+            "[line 1] Error at ';': Expect ')' after expression.",
+            "[line 3] Error at ';': Expect expression."
+        ]
+    );
+}
+
+#[test]
+fn ternary_is_right_associative() {
+    assert_eq!(printed("a ? b : c ? d : e"), "(?: a b (?: c d e))");
+}
+
+#[test]
+fn printer_styles() {
+    let expr = parse("1 + 2 * 3").unwrap();
+    assert_eq!(
+        AstPrinter::new(PrintStyle::Lisp).print(&expr),
+        "(+ 1.0 (* 2.0 3.0))"
+    );
+    assert_eq!(
+        AstPrinter::new(PrintStyle::ReversePolish).print(&expr),
+        "1.0 2.0 3.0 * +"
+    );
+}
+
+#[test]
+fn expression_json_nests_operands() {
+    assert_eq!(
+        to_json(&parse("-1 + 2").unwrap()),
+        "{\"type\": \"Binary\", \"line\": 1, \"operator\": \"+\", \
+         \"left\": {\"type\": \"Unary\", \"line\": 1, \"operator\": \"-\", \
+         \"right\": {\"type\": \"Literal\", \"line\": 1, \"value\": 1.0}}, \
+         \"right\": {\"type\": \"Literal\", \"line\": 1, \"value\": 2.0}}"
+    );
+}
+
+#[test]
+fn expression_json_renders_arrays() {
+    assert_eq!(
+        to_json(&parse("f([1], {\"k\": nil})").unwrap()),
+        "{\"type\": \"Call\", \"line\": 1, \
+         \"callee\": {\"type\": \"Variable\", \"line\": 1, \"name\": \"f\"}, \
+         \"arguments\": [{\"type\": \"List\", \"line\": 1, \
+         \"elements\": [{\"type\": \"Literal\", \"line\": 1, \"value\": 1.0}]}, \
+         {\"type\": \"Map\", \"line\": 1, \
+         \"entries\": [{\"key\": {\"type\": \"Literal\", \"line\": 1, \"value\": \"k\"}, \
+         \"value\": {\"type\": \"Literal\", \"line\": 1, \"value\": null}}]}]}"
+    );
+}
+
+#[test]
+fn program_json_covers_statements() {
+    let statements = parse_program("var a;\nif (a) print a; else return;").unwrap();
+    assert_eq!(
+        program_to_json(&statements),
+        "[{\"type\": \"Var\", \"line\": 1, \"name\": \"a\", \"initializer\": null}, \
+         {\"type\": \"If\", \"line\": 2, \
+         \"condition\": {\"type\": \"Variable\", \"line\": 2, \"name\": \"a\"}, \
+         \"then\": {\"type\": \"Print\", \"line\": 2, \
+         \"values\": [{\"type\": \"Variable\", \"line\": 2, \"name\": \"a\"}]}, \
+         \"else\": {\"type\": \"Return\", \"line\": 2, \"value\": null}}]"
+    );
+}
+
+#[test]
+fn static_methods_reject_this_and_super() {
+    assert_eq!(
+        program_errors("class A { static f() { return this; } }"),
+        ["[line 1] Error at 'this': Can't use 'this' in a static method."]
+    );
+    assert_eq!(
+        program_errors("class A {}\nclass B < A { static g() { return super.f(); } }"),
+        ["[line 2] Error at 'super': Can't use 'super' in a static method."]
+    );
+    // A class declared inside a static method gets its own `this`.
+    assert!(parse_program("class A { static f() { class B { m() { return this; } } } }").is_ok());
+}
+
+#[test]
+fn cursor_keeps_returning_eof_past_the_end() {
+    let mut cursor = TokenCursor::new(tokenize_checked("a").0);
+    assert_eq!(*cursor.peek_nth(5), Token::EOF);
+    assert!(cursor.match_token(TokenType::Identifier));
+    assert_eq!(*cursor.advance(), Token::EOF);
+    assert_eq!(*cursor.advance(), Token::EOF);
+    assert!(cursor.check(TokenType::EOF));
+}
+
+#[test]
+fn cursor_restores_a_saved_position() {
+    let mut cursor = TokenCursor::new(tokenize_checked("a b c").0);
+    cursor.advance();
+    let saved = cursor.save();
+    cursor.advance();
+    cursor.advance();
+    assert_eq!(*cursor.peek(), Token::EOF);
+    cursor.restore(saved);
+    assert_eq!(*cursor.peek(), Token::Identifier("b".into()));
+}
+
+#[test]
+fn deep_nesting_is_an_error_rather_than_a_crash() {
+    let source = format!("{}1{}", "(".repeat(1000), ")".repeat(1000));
+    // `MAX_DEPTH` is sized for the main thread's stack; test threads get less.
+    let errors = std::thread::Builder::new()
+        .stack_size(8 << 20)
+        .spawn(move || parse(&source).unwrap_err())
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.ends_with("Too many nested expressions."));
+
+    let shallow = format!("{}1{}", "(".repeat(10), ")".repeat(10));
+    assert!(parse(&shallow).is_ok());
+    let tokens = tokenize_checked(&shallow).0;
+    assert!(Parser::with_max_depth(tokens, 5).parse().is_err());
+}
+
+#[test]
+fn more_than_255_arguments_or_parameters_is_an_error() {
+    let arguments = vec!["1"; 256].join(", ");
+    assert_eq!(
+        program_errors(&format!("f({});", arguments)),
+        ["[line 1] Error at '1': Can't have more than 255 arguments."]
+    );
+    let params = (0..256).map(|i| format!("p{}", i)).collect::<Vec<_>>();
+    assert_eq!(
+        program_errors(&format!("fun f({}) {{}}", params.join(", "))),
+        ["[line 1] Error at 'p255': Can't have more than 255 parameters."]
+    );
+    assert!(parse_program(&format!("f({});", vec!["1"; 255].join(", "))).is_ok());
+}
+
+#[test]
+fn print_rejects_a_trailing_comma() {
+    assert_eq!(
+        program_errors("print 1, 2,;"),
+        ["[line 1] Error at ';': Expect expression."]
+    );
+}
+
+#[test]
+fn assigning_to_a_non_variable_is_an_error_at_the_equals() {
+    assert_eq!(
+        program_errors("1 = 2;"),
+        ["[line 1] Error at '=': Invalid assignment target."]
+    );
+    assert_eq!(
+        program_errors("var a; (a) = 2;"),
+        ["[line 1] Error at '=': Invalid assignment target."]
+    );
+}
+
+#[test]
+fn expression_must_be_the_whole_input() {
+    assert_eq!(
+        parse("1 2").unwrap_err()[0].to_string(),
+        "[line 1] Error at '2': Expect end of expression."
+    );
+    assert_eq!(
+        parse("1 + 2;").unwrap_err()[0].message,
+        "Error at ';': Expect end of expression."
+    );
+    assert!(parse("1 + 2").is_ok());
+}
+
+#[test]
+fn this_outside_a_class_is_an_error() {
+    assert_eq!(
+        program_errors("print this;"),
+        ["[line 1] Error at 'this': Can't use 'this' outside of a class."]
+    );
+    assert_eq!(
+        program_errors("fun f() {\n  return this;\n}"),
+        ["[line 2] Error at 'this': Can't use 'this' outside of a class."]
+    );
+    // Functions nested in a method still see the method's `this`.
+    assert!(parse_program("class A { m() { fun f() { return this; } } }").is_ok());
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::parser::{Expr, FunctionDecl, ParseError, Stmt};
+
+#[cfg(test)]
+mod tests;
+
+// Static pass run between parsing and interpretation. It records on every
+// variable reference how many scopes separate it from its declaration, so a
+// closure keeps seeing the binding that was in scope where it was written.
+pub struct Resolver {
+    // Innermost scope last.
+    scopes: Vec<HashMap<String, Binding>>,
+    errors: Vec<ParseError>,
+    // Whether the code being resolved is inside a function body.
+    in_function: bool,
+    // Set to collect `warnings` for locals that are never read.
+    warn_unused: bool,
+    warnings: Vec<Diagnostic>,
+}
+
+struct Binding {
+    // `false` while the variable's own initializer is being resolved.
+    defined: bool,
+    used: bool,
+    // The `var` declaration's line; `None` for bindings that are never
+    // reported as unused, like parameters and `this`.
+    line: Option<usize>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            scopes: Vec::new(),
+            errors: Vec::new(),
+            in_function: false,
+            warn_unused: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn with_unused_warnings() -> Self {
+        Self {
+            warn_unused: true,
+            ..Self::new()
+        }
+    }
+
+    // Ordered by line. Collected by `resolve` even when it fails.
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    pub fn resolve(&mut self, statements: &mut [Stmt]) -> Result<(), Vec<ParseError>> {
+        self.resolve_all(statements);
+        self.warnings.sort_by_key(|warning| warning.line);
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn resolve_all(&mut self, statements: &mut [Stmt]) {
+        for stmt in statements {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Expression(expr) => self.resolve_expr(expr),
+            Stmt::Print { values, .. } => {
+                for value in values {
+                    self.resolve_expr(value);
+                }
+            }
+            Stmt::Block { statements, .. } => {
+                self.begin_scope();
+                self.resolve_all(statements);
+                self.end_scope();
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.resolve_expr(condition);
+                self.resolve_stmt(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_stmt(else_branch);
+                }
+            }
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                ..
+            } => {
+                self.resolve_expr(condition);
+                self.resolve_stmt(body);
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment);
+                }
+            }
+            Stmt::Var {
+                name,
+                initializer,
+                line,
+            } => {
+                self.declare(name, *line);
+                if let Some(scope) = self.scopes.last_mut() {
+                    if let Some(binding) = scope.get_mut(name.as_str()) {
+                        binding.line = Some(*line);
+                    }
+                }
+                if let Some(initializer) = initializer {
+                    self.resolve_expr(initializer);
+                }
+                self.define(name);
+            }
+            Stmt::Function(declaration) => {
+                self.declare(&declaration.name, declaration.line);
+                self.define(&declaration.name);
+                self.resolve_function(declaration);
+            }
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+                static_methods,
+                line,
+            } => {
+                self.declare(name, *line);
+                self.define(name);
+                if let Some(superclass) = superclass {
+                    self.resolve_expr(superclass);
+                    self.begin_scope();
+                    self.define("super");
+                }
+                // Static methods are never bound, so they don't see `this`.
+                for method in static_methods {
+                    self.resolve_function(method);
+                }
+                self.begin_scope();
+                self.define("this");
+                for method in methods {
+                    self.resolve_function(method);
+                }
+                self.end_scope();
+                if superclass.is_some() {
+                    self.end_scope();
+                }
+            }
+            Stmt::Return { value, line } => {
+                if !self.in_function {
+                    self.error("return", *line, "Can't return from top-level code.");
+                }
+                if let Some(value) = value {
+                    self.resolve_expr(value);
+                }
+            }
+            Stmt::Break { .. } | Stmt::Continue { .. } => {}
+        }
+    }
+
+    // Parameters and body share one scope, matching how calls bind them.
+    fn resolve_function(&mut self, declaration: &mut FunctionDecl) {
+        let enclosing = std::mem::replace(&mut self.in_function, true);
+        self.begin_scope();
+        for param in &declaration.params {
+            self.declare(param, declaration.line);
+            self.define(param);
+        }
+        self.resolve_all(&mut declaration.body);
+        self.end_scope();
+        self.in_function = enclosing;
+    }
+
+    fn resolve_expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal { .. } => {}
+            Expr::Grouping { expression, .. } => self.resolve_expr(expression),
+            Expr::Unary { right, .. } => self.resolve_expr(right),
+            Expr::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.resolve_expr(condition);
+                self.resolve_expr(then_branch);
+                self.resolve_expr(else_branch);
+            }
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                self.resolve_expr(left);
+                self.resolve_expr(right);
+            }
+            Expr::Variable { name, line, depth } => {
+                if self
+                    .scopes
+                    .last()
+                    .and_then(|scope| scope.get(name.as_str()))
+                    .is_some_and(|binding| !binding.defined)
+                {
+                    self.error(
+                        name,
+                        *line,
+                        "Can't read local variable in its own initializer.",
+                    );
+                }
+                *depth = self.resolve_local(name);
+                if let Some(depth) = *depth {
+                    let scope = self.scopes.len() - 1 - depth;
+                    if let Some(binding) = self.scopes[scope].get_mut(name.as_str()) {
+                        binding.used = true;
+                    }
+                }
+            }
+            Expr::Assign {
+                name, value, depth, ..
+            } => {
+                self.resolve_expr(value);
+                *depth = self.resolve_local(name);
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                self.resolve_expr(callee);
+                for argument in arguments {
+                    self.resolve_expr(argument);
+                }
+            }
+            Expr::List { elements, .. } => {
+                for element in elements {
+                    self.resolve_expr(element);
+                }
+            }
+            Expr::Map { entries, .. } => {
+                for (key, value) in entries {
+                    self.resolve_expr(key);
+                    self.resolve_expr(value);
+                }
+            }
+            Expr::Index { object, index, .. } => {
+                self.resolve_expr(object);
+                self.resolve_expr(index);
+            }
+            Expr::IndexSet {
+                object,
+                index,
+                value,
+                ..
+            } => {
+                self.resolve_expr(value);
+                self.resolve_expr(object);
+                self.resolve_expr(index);
+            }
+            Expr::Get { object, .. } | Expr::OptionalGet { object, .. } => {
+                self.resolve_expr(object)
+            }
+            Expr::Set { object, value, .. } | Expr::CompoundSet { object, value, .. } => {
+                self.resolve_expr(value);
+                self.resolve_expr(object);
+            }
+            Expr::This { depth, .. } => *depth = self.resolve_local("this"),
+            Expr::Super { depth, .. } => *depth = self.resolve_local("super"),
+        }
+    }
+
+    // `None` leaves the name to be looked up in the globals at runtime.
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name))
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+        if !self.warn_unused {
+            return;
+        }
+        for (name, binding) in scope {
+            if let (Some(line), false) = (binding.line, binding.used) {
+                self.warnings.push(Diagnostic {
+                    line,
+                    column: 0,
+                    severity: Severity::Warning,
+                    message: format!("Local variable '{}' is never used.", name),
+                });
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &str, line: usize) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        let binding = Binding {
+            defined: false,
+            used: false,
+            line: None,
+        };
+        if scope.insert(name.to_string(), binding).is_some() {
+            self.error(
+                name,
+                line,
+                "Already a variable with this name in this scope.",
+            );
+        }
+    }
+
+    fn define(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope
+                .entry(name.to_string())
+                .or_insert(Binding {
+                    defined: false,
+                    used: false,
+                    line: None,
+                })
+                .defined = true;
+        }
+    }
+
+    fn error(&mut self, name: &str, line: usize, message: &str) {
+        self.errors.push(ParseError {
+            line,
+            message: format!("Error at '{}': {}", name, message),
+        });
+    }
+}
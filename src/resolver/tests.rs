@@ -0,0 +1,62 @@
+use super::*;
+use crate::parser::Parser;
+use crate::tokenizer::tokenize_checked;
+
+// Rendered unused-local warnings, for sources that resolve.
+fn warnings(source: &str) -> Vec<String> {
+    let mut statements = Parser::new(tokenize_checked(source).0)
+        .parse_program()
+        .expect("source should parse");
+    let mut resolver = Resolver::with_unused_warnings();
+    resolver
+        .resolve(&mut statements)
+        .expect("source should resolve");
+    resolver
+        .warnings()
+        .iter()
+        .map(Diagnostic::to_string)
+        .collect()
+}
+
+#[test]
+fn unused_local_is_reported_at_its_declaration() {
+    assert_eq!(
+        warnings("{\n  var used = 1;\n  var unused = 2;\n  print used;\n}"),
+        ["[line 3] Warning: Local variable 'unused' is never used."]
+    );
+}
+
+#[test]
+fn used_locals_parameters_and_globals_are_silent() {
+    assert!(warnings("var global = 1; fun f(param) { var local = 2; return local; }").is_empty());
+}
+
+#[test]
+fn warnings_are_off_by_default() {
+    let mut statements = Parser::new(tokenize_checked("{ var unused; }").0)
+        .parse_program()
+        .unwrap();
+    let mut resolver = Resolver::new();
+    resolver.resolve(&mut statements).unwrap();
+    assert!(resolver.warnings().is_empty());
+}
+
+#[test]
+fn top_level_return_is_an_error_at_its_line() {
+    let mut statements = Parser::new(tokenize_checked("print 1;\n\nreturn;").0)
+        .parse_program()
+        .unwrap();
+    let errors = Resolver::new().resolve(&mut statements).unwrap_err();
+    assert_eq!(
+        errors.iter().map(ParseError::to_string).collect::<Vec<_>>(),
+        ["[line 3] Error at 'return': Can't return from top-level code."]
+    );
+}
+
+#[test]
+fn return_inside_a_function_resolves() {
+    let mut statements = Parser::new(tokenize_checked("fun f() { return; }").0)
+        .parse_program()
+        .unwrap();
+    assert!(Resolver::new().resolve(&mut statements).is_ok());
+}
@@ -0,0 +1,47 @@
+use super::{Spanned, Token};
+
+// Renders tokens as a JSON array, one object per line. Invalid tokens are
+// left out; errors are reported separately.
+pub fn to_json(tokens: &[Spanned<Token>]) -> String {
+    let objects = tokens
+        .iter()
+        .filter(|spanned| !matches!(spanned.token, Token::Invalid(_)))
+        .map(|spanned| {
+            format!(
+                "  {{\"type\": {}, \"lexeme\": {}, \"literal\": {}, \"line\": {}}}",
+                json_string(&spanned.token.token_type().to_string()),
+                json_string(&spanned.token.lexeme()),
+                literal(&spanned.token),
+                spanned.line
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("[\n{}\n]", objects.join(",\n"))
+}
+
+fn literal(token: &Token) -> String {
+    match token {
+        Token::String(s) => json_string(s),
+        Token::Char(c) => json_string(&c.to_string()),
+        Token::Number { value, .. } => format!("{:?}", value),
+        _ => "null".to_string(),
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
@@ -1,85 +1,115 @@
-use peekmore::{PeekMore, PeekMoreIterator};
-use std::{fmt::Display, str::Chars};
+use scanner::{source_lines, Scanner};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::io::{self, Read};
+use std::rc::Rc;
 
-struct Keyword {
-    name: &'static str,
-    token: Token,
-}
+pub(crate) use json::json_string;
+pub use json::to_json;
+pub use render::{render_error, render_error_with_options};
+pub use source::tokens_to_source;
 
-const RESERVED_KEYWORDS: [Keyword; 16] = [
-    Keyword {
-        name: "var",
-        token: Token::Var,
-    },
-    Keyword {
-        name: "and",
-        token: Token::And,
-    },
-    Keyword {
-        name: "class",
-        token: Token::Class,
-    },
-    Keyword {
-        name: "else",
-        token: Token::Else,
-    },
-    Keyword {
-        name: "false",
-        token: Token::False,
-    },
-    Keyword {
-        name: "for",
-        token: Token::For,
-    },
-    Keyword {
-        name: "fun",
-        token: Token::Fun,
-    },
-    Keyword {
-        name: "if",
-        token: Token::If,
-    },
-    Keyword {
-        name: "nil",
-        token: Token::Nil,
-    },
-    Keyword {
-        name: "or",
-        token: Token::Or,
-    },
-    Keyword {
-        name: "print",
-        token: Token::Print,
-    },
-    Keyword {
-        name: "return",
-        token: Token::Return,
-    },
-    Keyword {
-        name: "super",
-        token: Token::Super,
-    },
-    Keyword {
-        name: "this",
-        token: Token::This,
-    },
-    Keyword {
-        name: "true",
-        token: Token::True,
-    },
-    Keyword {
-        name: "while",
-        token: Token::While,
-    },
-];
+mod json;
+mod render;
+mod scanner;
+mod source;
+#[cfg(test)]
+mod tests;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TokenizerError {
     pub line: usize,
+    pub column: usize,
     pub message: String,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spanned<T> {
+    pub token: T,
+    pub line: usize,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum TokenType {
+    // Keywords
+    And,
+    Class,
+    Else,
+    False,
+    For,
+    Fun,
+    If,
+    Nil,
+    Or,
+    Print,
+    Return,
+    Super,
+    This,
+    True,
+    Var,
+    While,
+    Break,
+    Continue,
+
+    // Single-character tokens
+    Equal,
+    EqualEqual,
+    Semicolon,
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Star,
+    Dot,
+    Comma,
+    Question,
+    QuestionDot,
+    Colon,
+    Plus,
+    Minus,
+    Slash,
+    Percent,
+    Bang,
+    BangEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    AmpAmp,
+    PipePipe,
+
+    // Literals
+    Identifier,
+    String,
+    Char,
+    Number,
+    Invalid,
+
+    // Whitespace and comments
+    WhiteSpace,
+    LineComment,
+    BlockComment,
+
+    // End of file
+    EOF,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Token {
     // Keywords
     And,
@@ -98,6 +128,8 @@ pub enum Token {
     True,
     Var,
     While,
+    Break,
+    Continue,
 
     // Single-character tokens
     Equal,
@@ -107,36 +139,267 @@ pub enum Token {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Star,
     Dot,
     Comma,
+    Question,
+    QuestionDot,
+    Colon,
     Plus,
     Minus,
     Slash,
+    Percent,
     Bang,
     BangEqual,
     Less,
     LessEqual,
     Greater,
     GreaterEqual,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    AmpAmp,
+    PipePipe,
 
     // Literals
-    Identifier(String),
+    Identifier(Rc<str>),
     String(String),
-    Number(String),
+    Char(char),
+    // `raw` is the lexeme with digit separators removed; `value` is computed
+    // once while scanning so nothing has to parse `raw` again. `is_integer`
+    // is set when the literal had neither a decimal point nor an exponent.
+    Number {
+        raw: String,
+        value: f64,
+        is_integer: bool,
+    },
     Invalid(TokenizerError),
 
-    // Whitespace
-    WhiteSpace,
+    // Whitespace and comments. Both are skipped unless asked for; a
+    // `WhiteSpace` token is a whole run and, when kept, carries its text. A
+    // leading byte-order mark or `#!` line is kept as one too.
+    WhiteSpace(String),
+    LineComment(String),
+    BlockComment(String),
 
     // End of file
     EOF,
 }
 
+impl Token {
+    pub fn token_type(&self) -> TokenType {
+        match self {
+            Token::And => TokenType::And,
+            Token::Class => TokenType::Class,
+            Token::Else => TokenType::Else,
+            Token::False => TokenType::False,
+            Token::For => TokenType::For,
+            Token::Fun => TokenType::Fun,
+            Token::If => TokenType::If,
+            Token::Nil => TokenType::Nil,
+            Token::Or => TokenType::Or,
+            Token::Print => TokenType::Print,
+            Token::Return => TokenType::Return,
+            Token::Super => TokenType::Super,
+            Token::This => TokenType::This,
+            Token::True => TokenType::True,
+            Token::Var => TokenType::Var,
+            Token::While => TokenType::While,
+            Token::Break => TokenType::Break,
+            Token::Continue => TokenType::Continue,
+            Token::Equal => TokenType::Equal,
+            Token::EqualEqual => TokenType::EqualEqual,
+            Token::Semicolon => TokenType::Semicolon,
+            Token::LeftParen => TokenType::LeftParen,
+            Token::RightParen => TokenType::RightParen,
+            Token::LeftBrace => TokenType::LeftBrace,
+            Token::RightBrace => TokenType::RightBrace,
+            Token::LeftBracket => TokenType::LeftBracket,
+            Token::RightBracket => TokenType::RightBracket,
+            Token::Star => TokenType::Star,
+            Token::Dot => TokenType::Dot,
+            Token::Comma => TokenType::Comma,
+            Token::Question => TokenType::Question,
+            Token::QuestionDot => TokenType::QuestionDot,
+            Token::Colon => TokenType::Colon,
+            Token::Plus => TokenType::Plus,
+            Token::Minus => TokenType::Minus,
+            Token::Slash => TokenType::Slash,
+            Token::Percent => TokenType::Percent,
+            Token::Bang => TokenType::Bang,
+            Token::BangEqual => TokenType::BangEqual,
+            Token::Less => TokenType::Less,
+            Token::LessEqual => TokenType::LessEqual,
+            Token::Greater => TokenType::Greater,
+            Token::GreaterEqual => TokenType::GreaterEqual,
+            Token::PlusEqual => TokenType::PlusEqual,
+            Token::MinusEqual => TokenType::MinusEqual,
+            Token::StarEqual => TokenType::StarEqual,
+            Token::SlashEqual => TokenType::SlashEqual,
+            Token::AmpAmp => TokenType::AmpAmp,
+            Token::PipePipe => TokenType::PipePipe,
+            Token::Identifier(_) => TokenType::Identifier,
+            Token::String(_) => TokenType::String,
+            Token::Char(_) => TokenType::Char,
+            Token::Number { .. } => TokenType::Number,
+            Token::Invalid(_) => TokenType::Invalid,
+            Token::WhiteSpace(_) => TokenType::WhiteSpace,
+            Token::LineComment(_) => TokenType::LineComment,
+            Token::BlockComment(_) => TokenType::BlockComment,
+            Token::EOF => TokenType::EOF,
+        }
+    }
+
+    // `3` and `0x1F` are integers, `3.0` and `3e0` are not. False for
+    // anything that isn't a number.
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Token::Number {
+                is_integer: true,
+                ..
+            }
+        )
+    }
+
+    pub fn lexeme(&self) -> String {
+        match self {
+            Token::And => "and".to_string(),
+            Token::Class => "class".to_string(),
+            Token::Else => "else".to_string(),
+            Token::False => "false".to_string(),
+            Token::For => "for".to_string(),
+            Token::Fun => "fun".to_string(),
+            Token::If => "if".to_string(),
+            Token::Nil => "nil".to_string(),
+            Token::Or => "or".to_string(),
+            Token::Print => "print".to_string(),
+            Token::Return => "return".to_string(),
+            Token::Super => "super".to_string(),
+            Token::This => "this".to_string(),
+            Token::True => "true".to_string(),
+            Token::Var => "var".to_string(),
+            Token::While => "while".to_string(),
+            Token::Break => "break".to_string(),
+            Token::Continue => "continue".to_string(),
+            Token::Equal => "=".to_string(),
+            Token::EqualEqual => "==".to_string(),
+            Token::Semicolon => ";".to_string(),
+            Token::LeftParen => "(".to_string(),
+            Token::RightParen => ")".to_string(),
+            Token::LeftBrace => "{".to_string(),
+            Token::RightBrace => "}".to_string(),
+            Token::LeftBracket => "[".to_string(),
+            Token::RightBracket => "]".to_string(),
+            Token::Star => "*".to_string(),
+            Token::Dot => ".".to_string(),
+            Token::Comma => ",".to_string(),
+            Token::Question => "?".to_string(),
+            Token::QuestionDot => "?.".to_string(),
+            Token::Colon => ":".to_string(),
+            Token::Plus => "+".to_string(),
+            Token::Minus => "-".to_string(),
+            Token::Slash => "/".to_string(),
+            Token::Percent => "%".to_string(),
+            Token::Bang => "!".to_string(),
+            Token::BangEqual => "!=".to_string(),
+            Token::Less => "<".to_string(),
+            Token::LessEqual => "<=".to_string(),
+            Token::Greater => ">".to_string(),
+            Token::GreaterEqual => ">=".to_string(),
+            Token::PlusEqual => "+=".to_string(),
+            Token::MinusEqual => "-=".to_string(),
+            Token::StarEqual => "*=".to_string(),
+            Token::SlashEqual => "/=".to_string(),
+            Token::AmpAmp => "&&".to_string(),
+            Token::PipePipe => "||".to_string(),
+            Token::Identifier(s) => s.to_string(),
+            Token::Number { raw, .. } => raw.clone(),
+            Token::String(s) => format!("\"{}\"", s),
+            Token::Char(c) => format!("'{}'", c),
+            Token::LineComment(s) => format!("//{}", s),
+            Token::BlockComment(s) => format!("/*{}*/", s),
+            Token::WhiteSpace(s) => s.clone(),
+            Token::Invalid(_) | Token::EOF => String::new(),
+        }
+    }
+}
+
+impl Display for TokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TokenType::And => "AND",
+            TokenType::Class => "CLASS",
+            TokenType::Else => "ELSE",
+            TokenType::False => "FALSE",
+            TokenType::For => "FOR",
+            TokenType::Fun => "FUN",
+            TokenType::If => "IF",
+            TokenType::Nil => "NIL",
+            TokenType::Or => "OR",
+            TokenType::Print => "PRINT",
+            TokenType::Return => "RETURN",
+            TokenType::Super => "SUPER",
+            TokenType::This => "THIS",
+            TokenType::True => "TRUE",
+            TokenType::Var => "VAR",
+            TokenType::While => "WHILE",
+            TokenType::Break => "BREAK",
+            TokenType::Continue => "CONTINUE",
+            TokenType::Equal => "EQUAL",
+            TokenType::EqualEqual => "EQUAL_EQUAL",
+            TokenType::Semicolon => "SEMICOLON",
+            TokenType::LeftParen => "LEFT_PAREN",
+            TokenType::RightParen => "RIGHT_PAREN",
+            TokenType::LeftBrace => "LEFT_BRACE",
+            TokenType::RightBrace => "RIGHT_BRACE",
+            TokenType::LeftBracket => "LEFT_BRACKET",
+            TokenType::RightBracket => "RIGHT_BRACKET",
+            TokenType::Star => "STAR",
+            TokenType::Dot => "DOT",
+            TokenType::Comma => "COMMA",
+            TokenType::Question => "QUESTION",
+            TokenType::QuestionDot => "QUESTION_DOT",
+            TokenType::Colon => "COLON",
+            TokenType::Plus => "PLUS",
+            TokenType::Minus => "MINUS",
+            TokenType::Slash => "SLASH",
+            TokenType::Percent => "PERCENT",
+            TokenType::Bang => "BANG",
+            TokenType::BangEqual => "BANG_EQUAL",
+            TokenType::Less => "LESS",
+            TokenType::LessEqual => "LESS_EQUAL",
+            TokenType::Greater => "GREATER",
+            TokenType::GreaterEqual => "GREATER_EQUAL",
+            TokenType::PlusEqual => "PLUS_EQUAL",
+            TokenType::MinusEqual => "MINUS_EQUAL",
+            TokenType::StarEqual => "STAR_EQUAL",
+            TokenType::SlashEqual => "SLASH_EQUAL",
+            TokenType::AmpAmp => "AND_AND",
+            TokenType::PipePipe => "OR_OR",
+            TokenType::Identifier => "IDENTIFIER",
+            TokenType::String => "STRING",
+            TokenType::Char => "CHAR",
+            TokenType::Number => "NUMBER",
+            TokenType::Invalid => "INVALID",
+            TokenType::WhiteSpace => "WHITESPACE",
+            TokenType::LineComment => "LINE_COMMENT",
+            TokenType::BlockComment => "BLOCK_COMMENT",
+            TokenType::EOF => "EOF",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Token::WhiteSpace => write!(f, ""),
+            Token::WhiteSpace(_) => write!(f, ""),
+            Token::LineComment(s) => write!(f, "LINE_COMMENT //{} {}", s, s),
+            Token::BlockComment(s) => write!(f, "BLOCK_COMMENT /*{}*/ {}", s, s),
             Token::And => write!(f, "AND and null"),
             Token::Class => write!(f, "CLASS class null"),
             Token::Else => write!(f, "ELSE else null"),
@@ -152,6 +415,8 @@ impl Display for Token {
             Token::This => write!(f, "THIS this null"),
             Token::True => write!(f, "TRUE true null"),
             Token::While => write!(f, "WHILE while null"),
+            Token::Break => write!(f, "BREAK break null"),
+            Token::Continue => write!(f, "CONTINUE continue null"),
             Token::Var => write!(f, "VAR var null"),
             Token::Equal => write!(f, "EQUAL = null"),
             Token::EqualEqual => write!(f, "EQUAL_EQUAL == null"),
@@ -160,12 +425,18 @@ impl Display for Token {
             Token::RightParen => write!(f, "RIGHT_PAREN ) null"),
             Token::LeftBrace => write!(f, "LEFT_BRACE {{ null"),
             Token::RightBrace => write!(f, "RIGHT_BRACE }} null"),
+            Token::LeftBracket => write!(f, "LEFT_BRACKET [ null"),
+            Token::RightBracket => write!(f, "RIGHT_BRACKET ] null"),
             Token::Star => write!(f, "STAR * null"),
             Token::Dot => write!(f, "DOT . null"),
             Token::Comma => write!(f, "COMMA , null"),
+            Token::Question => write!(f, "QUESTION ? null"),
+            Token::QuestionDot => write!(f, "QUESTION_DOT ?. null"),
+            Token::Colon => write!(f, "COLON : null"),
             Token::Plus => write!(f, "PLUS + null"),
             Token::Minus => write!(f, "MINUS - null"),
             Token::Slash => write!(f, "SLASH / null"),
+            Token::Percent => write!(f, "PERCENT % null"),
             Token::EOF => write!(f, "EOF  null"),
             Token::Bang => write!(f, "BANG ! null"),
             Token::BangEqual => write!(f, "BANG_EQUAL != null"),
@@ -173,28 +444,263 @@ impl Display for Token {
             Token::LessEqual => write!(f, "LESS_EQUAL <= null"),
             Token::Greater => write!(f, "GREATER > null"),
             Token::GreaterEqual => write!(f, "GREATER_EQUAL >= null"),
+            Token::PlusEqual => write!(f, "PLUS_EQUAL += null"),
+            Token::MinusEqual => write!(f, "MINUS_EQUAL -= null"),
+            Token::StarEqual => write!(f, "STAR_EQUAL *= null"),
+            Token::SlashEqual => write!(f, "SLASH_EQUAL /= null"),
+            Token::AmpAmp => write!(f, "AND_AND && null"),
+            Token::PipePipe => write!(f, "OR_OR || null"),
             Token::Identifier(s) => write!(f, "IDENTIFIER {} null", s),
             Token::String(s) => write!(f, "STRING \"{}\" {}", s, s),
-            Token::Number(s) => {
-                let f64_str = match s.parse::<f64>() {
-                    Ok(f) => format!("{:?}", f),
-                    Err(_) => s.clone(),
-                };
-                write!(f, "NUMBER {} {}", s, f64_str)
+            Token::Char(c) => write!(f, "CHAR '{}' {}", c, c),
+            Token::Number { raw, value, .. } => write!(f, "NUMBER {} {:?}", raw, value),
+            Token::Invalid(s) => write!(
+                f,
+                "[line {}, col {}] Error: {}",
+                s.line, s.column, s.message
+            ),
+        }
+    }
+}
+
+impl<T: Display> Display for Spanned<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.token.fmt(f)
+    }
+}
+
+// The `tokenize` command's output: one token per line. Invalid tokens are
+// left out since they are reported as errors instead.
+pub struct TokenList<'a>(pub &'a [Spanned<Token>]);
+
+impl Display for TokenList<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for spanned in self.0 {
+            if matches!(spanned.token, Token::Invalid(_) | Token::WhiteSpace(_)) {
+                continue;
+            }
+            if !first {
+                writeln!(f)?;
             }
-            Token::Invalid(s) => write!(f, "[line {}] Error: {}", s.line, s.message),
+            write!(f, "{}", spanned)?;
+            first = false;
         }
+        Ok(())
     }
 }
 
-pub fn tokenize(input: &str) -> Vec<Token> {
-    let mut tokens = Vec::new();
-    let mut chars = input.chars().peekmore();
-    let mut line = 1;
+// `tokenize --tokens-only`: how many tokens of each type were found, most
+// frequent first, followed by the number of lines in the source.
+pub struct TokenSummary<'a> {
+    pub tokens: &'a [Spanned<Token>],
+    pub source: &'a str,
+}
 
-    while let Some(c) = chars.next() {
+impl Display for TokenSummary<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut counts: Vec<(TokenType, usize)> = Vec::new();
+        for spanned in self.tokens {
+            if matches!(
+                spanned.token,
+                Token::Invalid(_) | Token::WhiteSpace(_) | Token::EOF
+            ) {
+                continue;
+            }
+            let token_type = spanned.token.token_type();
+            match counts.iter_mut().find(|(t, _)| *t == token_type) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((token_type, 1)),
+            }
+        }
+        // Stable, so ties keep the order the types first appeared in.
+        counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+        let counts = counts
+            .iter()
+            .map(|(token_type, count)| format!("{}: {}", token_type, count))
+            .collect::<Vec<_>>();
+        if !counts.is_empty() {
+            writeln!(f, "{}", counts.join(", "))?;
+        }
+        write!(f, "Lines: {}", source_lines(self.source).count())
+    }
+}
+
+// Everything that changes how source is scanned, set through chained
+// builder calls: `TokenizerOptions::default().keep_comments(true)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenizerOptions {
+    keep_comments: bool,
+    coalesce_errors: bool,
+    intern_identifiers: bool,
+    keep_whitespace: bool,
+    tab_width: usize,
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> Self {
+        Self {
+            keep_comments: false,
+            coalesce_errors: false,
+            intern_identifiers: false,
+            keep_whitespace: false,
+            tab_width: 1,
+        }
+    }
+}
+
+impl TokenizerOptions {
+    // Emit comments as tokens instead of skipping them.
+    pub fn keep_comments(mut self, keep_comments: bool) -> Self {
+        self.keep_comments = keep_comments;
+        self
+    }
+
+    // Report a run of unexpected characters as one error.
+    pub fn coalesce_errors(mut self, coalesce_errors: bool) -> Self {
+        self.coalesce_errors = coalesce_errors;
+        self
+    }
+
+    // Share one allocation between occurrences of the same identifier.
+    pub fn intern_identifiers(mut self, intern_identifiers: bool) -> Self {
+        self.intern_identifiers = intern_identifiers;
+        self
+    }
+
+    // Emit whitespace runs as tokens carrying the exact text.
+    pub fn keep_whitespace(mut self, keep_whitespace: bool) -> Self {
+        self.keep_whitespace = keep_whitespace;
+        self
+    }
+
+    // Columns between tab stops when reporting error columns.
+    pub fn tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+}
+
+pub struct Tokenizer<'a> {
+    chars: Scanner<'a>,
+    finished: bool,
+    // Emit comments as tokens instead of folding them into whitespace.
+    trivia: bool,
+    // Report a run of unexpected characters as one error instead of one each.
+    coalesce: bool,
+    // When set, repeated identifiers share one allocation.
+    interner: Option<HashSet<Rc<str>>>,
+    // Emit whitespace runs, with their text, instead of dropping them.
+    verbatim: bool,
+    // What the scanner skipped at the start, still to be emitted when verbatim.
+    prefix: Option<Spanned<Token>>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::with_options(input, &TokenizerOptions::default())
+    }
+
+    pub fn with_options(input: &'a str, options: &TokenizerOptions) -> Self {
+        let chars = Scanner::with_tab_width(input, options.tab_width);
+        let skipped = chars.offset();
+        let prefix = (options.keep_whitespace && skipped > 0).then(|| Spanned {
+            token: Token::WhiteSpace(input[..skipped].to_string()),
+            line: 1,
+            span: Span {
+                start: 0,
+                end: skipped,
+            },
+        });
+        Self {
+            chars,
+            finished: false,
+            trivia: options.keep_comments,
+            coalesce: options.coalesce_errors,
+            interner: options.intern_identifiers.then(HashSet::new),
+            verbatim: options.keep_whitespace,
+            prefix,
+        }
+    }
+
+    pub fn with_trivia(input: &'a str) -> Self {
+        Self::with_options(input, &TokenizerOptions::default().keep_comments(true))
+    }
+
+    pub fn with_coalesced_errors(input: &'a str) -> Self {
+        Self::with_options(input, &TokenizerOptions::default().coalesce_errors(true))
+    }
+
+    pub fn with_interning(input: &'a str) -> Self {
+        Self::with_options(input, &TokenizerOptions::default().intern_identifiers(true))
+    }
+}
+
+impl Iterator for Tokenizer<'_> {
+    type Item = Spanned<Token>;
+
+    // Whitespace only separates tokens; it never reaches the output unless
+    // the tokenizer is verbatim.
+    fn next(&mut self) -> Option<Spanned<Token>> {
+        if let Some(prefix) = self.prefix.take() {
+            return Some(prefix);
+        }
+        loop {
+            let spanned = self.scan_token()?;
+            if self.verbatim || !matches!(spanned.token, Token::WhiteSpace(_)) {
+                return Some(spanned);
+            }
+        }
+    }
+}
+
+impl Tokenizer<'_> {
+    fn scan_token(&mut self) -> Option<Spanned<Token>> {
+        if self.finished {
+            return None;
+        }
+        let (trivia, coalesce, verbatim) = (self.trivia, self.coalesce, self.verbatim);
+        let chars = &mut self.chars;
+        let interner = self.interner.as_mut();
+        let Some(c) = chars.next() else {
+            let eof = Spanned {
+                token: Token::EOF,
+                line: chars.line(),
+                span: Span {
+                    start: chars.offset(),
+                    end: chars.offset(),
+                },
+            };
+            self.finished = true;
+            return Some(eof);
+        };
+        let (line, column) = (chars.line(), chars.column());
+        let start = chars.offset() - c.len_utf8();
+        let error = |message| {
+            Token::Invalid(TokenizerError {
+                line,
+                column,
+                message,
+            })
+        };
         let token = match c {
-            ' ' | '\t' => Token::WhiteSpace,
+            ' ' | '\t' | '\n' | '\r' => {
+                // The text is only collected when it will be kept.
+                let mut text = String::new();
+                if verbatim {
+                    text.push(c);
+                }
+                while let Some(&c) = chars.peek() {
+                    if !matches!(c, ' ' | '\t' | '\n' | '\r') {
+                        break;
+                    }
+                    if verbatim {
+                        text.push(c);
+                    }
+                    chars.next();
+                }
+                Token::WhiteSpace(text)
+            }
             '=' => {
                 if let Some(&next_char) = chars.peek() {
                     if next_char == '=' {
@@ -212,21 +718,58 @@ pub fn tokenize(input: &str) -> Vec<Token> {
             ')' => Token::RightParen,
             '{' => Token::LeftBrace,
             '}' => Token::RightBrace,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '*' if chars.peek() == Some(&'=') => {
+                chars.next();
+                Token::StarEqual
+            }
             '*' => Token::Star,
             '.' => Token::Dot,
             ',' => Token::Comma,
+            '?' if chars.peek() == Some(&'.') => {
+                chars.next();
+                Token::QuestionDot
+            }
+            '?' => Token::Question,
+            ':' => Token::Colon,
+            '+' if chars.peek() == Some(&'=') => {
+                chars.next();
+                Token::PlusEqual
+            }
             '+' => Token::Plus,
+            '-' if chars.peek() == Some(&'=') => {
+                chars.next();
+                Token::MinusEqual
+            }
             '-' => Token::Minus,
+            '%' => Token::Percent,
             '/' => {
                 if let Some(&next_char) = chars.peek() {
                     if next_char == '/' {
-                        while let Some(c) = chars.next() {
-                            if c == '\n' {
-                                line += 1;
+                        chars.next();
+                        let mut text = String::new();
+                        while let Some(&c) = chars.peek() {
+                            if c == '\n' || c == '\r' {
                                 break;
                             }
+                            text.push(c);
+                            chars.next();
+                        }
+                        match trivia {
+                            true => Token::LineComment(text),
+                            false => Token::WhiteSpace(String::new()),
+                        }
+                    } else if next_char == '*' {
+                        chars.next();
+                        match tokenize_block_comment(chars) {
+                            Ok(text) if trivia => Token::BlockComment(text),
+                            Ok(_) => Token::WhiteSpace(String::new()),
+                            Err(message) => error(message),
                         }
-                        Token::WhiteSpace
+                    } else if next_char == '=' {
+                        chars.next();
+                        Token::SlashEqual
                     } else {
                         Token::Slash
                     }
@@ -270,49 +813,165 @@ pub fn tokenize(input: &str) -> Vec<Token> {
                     Token::Greater
                 }
             }
-            '0'..='9' => {
-                let number = tokenize_number(c, &mut chars);
-                number
-            }
-            '"' => {
-                let string = tokenize_string(&mut chars);
-                string
+            // Only the doubled forms exist; a lone `&` or `|` falls through to the error.
+            '&' if chars.peek() == Some(&'&') => {
+                chars.next();
+                Token::AmpAmp
             }
-            'a'..='z' | 'A'..='Z' | '_' => {
-                let identifier = tokenize_identifier(c, &mut chars);
-                identifier
+            '|' if chars.peek() == Some(&'|') => {
+                chars.next();
+                Token::PipePipe
             }
-            '\n' => {
-                line += 1;
-                Token::WhiteSpace
+            '0'..='9' => tokenize_number(c, chars).unwrap_or_else(error),
+            '"' => tokenize_string(chars).unwrap_or_else(error),
+            '\'' => tokenize_char(chars).unwrap_or_else(error),
+            '`' => tokenize_raw_string(chars).unwrap_or_else(error),
+            c if c.is_alphabetic() || c == '_' => tokenize_identifier(c, chars, interner),
+            c if c.is_control() => error(format!(
+                "Unexpected control character (0x{:02X}).",
+                c as u32
+            )),
+            _ if coalesce && chars.peek().is_some_and(|&c| is_unexpected(c)) => {
+                let mut run = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !is_unexpected(c) {
+                        break;
+                    }
+                    run.push(c);
+                    chars.next();
+                }
+                error(format!("Unexpected characters: {}", run))
             }
-            _ => Token::Invalid(TokenizerError {
-                line,
-                message: format!("Unexpected character: {}", c),
-            }),
+            _ => error(format!("Unexpected character: {}", c)),
         };
 
-        tokens.push(token);
+        let span = Span {
+            start,
+            end: chars.offset(),
+        };
+        Some(Spanned { token, line, span })
     }
+}
 
-    tokens.push(Token::EOF);
+pub fn tokenize(input: &str) -> Vec<Spanned<Token>> {
+    tokenize_with_options(input, &TokenizerOptions::default())
+}
+
+// Reads the whole source before scanning; a read failure, including input
+// that isn't UTF-8, is returned instead of being tokenized as empty.
+pub fn tokenize_reader<R: Read>(mut reader: R) -> io::Result<Vec<Spanned<Token>>> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(tokenize(&input))
+}
+
+// The general entry point; the other `tokenize_*` functions are shorthands
+// for a single option.
+pub fn tokenize_with_options(input: &str, options: &TokenizerOptions) -> Vec<Spanned<Token>> {
+    // Most tokens span a few chars, so a quarter of the input length avoids
+    // most regrowth without grossly over-allocating.
+    let mut tokens = Vec::with_capacity(input.len() / 4);
+    tokens.extend(Tokenizer::with_options(input, options));
     tokens
 }
 
-fn tokenize_number(first_char: char, chars: &mut PeekMoreIterator<Chars>) -> Token {
+// Same as `tokenize`, but comments come back as tokens carrying their text.
+pub fn tokenize_with_trivia(input: &str) -> Vec<Spanned<Token>> {
+    tokenize_with_options(input, &TokenizerOptions::default().keep_comments(true))
+}
+
+// Same as `tokenize`, but a run like `@#$` is a single invalid token.
+pub fn tokenize_coalesced(input: &str) -> Vec<Spanned<Token>> {
+    tokenize_with_options(input, &TokenizerOptions::default().coalesce_errors(true))
+}
+
+// Same as `tokenize`, but every occurrence of an identifier shares one `Rc<str>`.
+pub fn tokenize_interned(input: &str) -> Vec<Spanned<Token>> {
+    tokenize_with_options(input, &TokenizerOptions::default().intern_identifiers(true))
+}
+
+// Every char of the source ends up in exactly one token, in order, so a
+// formatter can keep indentation and comments. A leading byte-order mark or
+// `#!` line comes first, as whitespace. Rebuild the text from the spans,
+// `&input[span.start..span.end]`, not from lexemes: strings hold their
+// decoded text, numbers drop digit separators and errors have no lexeme.
+pub fn tokenize_verbatim(input: &str) -> Vec<Spanned<Token>> {
+    let options = TokenizerOptions::default()
+        .keep_comments(true)
+        .keep_whitespace(true);
+    tokenize_with_options(input, &options)
+}
+
+pub fn tokenize_spanned(input: &str) -> Vec<(Token, Span)> {
+    tokenize(input)
+        .into_iter()
+        .map(|spanned| (spanned.token, spanned.span))
+        .collect()
+}
+
+pub fn tokenize_checked(input: &str) -> (Vec<Spanned<Token>>, Vec<TokenizerError>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    for spanned in tokenize(input) {
+        match spanned.token {
+            Token::Invalid(e) => errors.push(e),
+            _ => tokens.push(spanned),
+        }
+    }
+    (tokens, errors)
+}
+
+// Fail-fast variant of `tokenize`: scanning stops at the first lexical error.
+pub fn tokenize_strict(input: &str) -> Result<Vec<Spanned<Token>>, TokenizerError> {
+    let mut tokens = Vec::new();
+    for spanned in Tokenizer::new(input) {
+        match spanned.token {
+            Token::Invalid(e) => return Err(e),
+            _ => tokens.push(spanned),
+        }
+    }
+    Ok(tokens)
+}
+
+// True for chars that can never start a token. `&` and `|` are excluded
+// since they may begin `&&` or `||`, and control chars are reported alone.
+fn is_unexpected(c: char) -> bool {
+    !(c.is_alphanumeric()
+        || c.is_control()
+        || c == '_'
+        || matches!(c, ' ' | '\t' | '\n' | '\r')
+        || "(){}[];,.?:=!<>+-*/%&|\"'`".contains(c))
+}
+
+fn tokenize_number(first_char: char, chars: &mut Scanner) -> Result<Token, String> {
+    if first_char == '0' {
+        match chars.peek() {
+            Some('x') | Some('X') => return tokenize_radix_number(chars, 16, "hex"),
+            Some('o') | Some('O') => return tokenize_radix_number(chars, 8, "octal"),
+            Some('b') | Some('B') => return tokenize_radix_number(chars, 2, "binary"),
+            _ => {}
+        }
+    }
     let mut number = String::new();
     let mut decimal = false;
+    let mut separators_valid = true;
+    let mut last_char = first_char;
     number.push(first_char);
     while let Some(&c) = chars.peek() {
-        if c.is_digit(10) {
+        if c.is_ascii_digit() {
             number.push(c);
             chars.next();
+        } else if c == '_' {
+            // Separators are dropped from the lexeme and may only sit between two digits.
+            let next_is_digit = chars.peek_nth(1).is_some_and(|c| c.is_ascii_digit());
+            separators_valid &= last_char.is_ascii_digit() && next_is_digit;
+            chars.next();
         } else if c == '.' {
             if decimal {
                 break;
             }
-            let next_2c = chars.peek_nth(2);
-            if next_2c.is_none() || !next_2c.unwrap().is_digit(10) {
+            // `peek_nth` counts from the peeked `.` at 0, so the digit after it is at 1.
+            if !chars.peek_nth(1).is_some_and(|c| c.is_ascii_digit()) {
                 break;
             }
             decimal = true;
@@ -321,33 +980,248 @@ fn tokenize_number(first_char: char, chars: &mut PeekMoreIterator<Chars>) -> Tok
         } else {
             break;
         }
+        last_char = c;
     }
-    if &number[number.len() - 1..] == "." {
-        number.push('0');
+    if !separators_valid {
+        return Err("Invalid number literal.".to_string());
+    }
+    let mut exponent = false;
+    if matches!(chars.peek(), Some('e') | Some('E')) {
+        let digit_at = match chars.peek_nth(1) {
+            Some('+') | Some('-') => 2,
+            _ => 1,
+        };
+        if chars.peek_nth(digit_at).is_some_and(|c| c.is_ascii_digit()) {
+            exponent = true;
+            for _ in 0..digit_at {
+                number.extend(chars.next());
+            }
+            while let Some(&c) = chars.peek() {
+                if !c.is_ascii_digit() {
+                    break;
+                }
+                number.push(c);
+                chars.next();
+            }
+        }
+    }
+    number_token(number, !decimal && !exponent)
+}
+
+fn tokenize_radix_number(chars: &mut Scanner, radix: u32, name: &str) -> Result<Token, String> {
+    let mut number = String::from('0');
+    number.extend(chars.next());
+    // Decimal digits are consumed even for binary and octal so that `0b2`
+    // is rejected as a whole instead of splitting into `0b` and `2`.
+    while let Some(&c) = chars.peek() {
+        if !c.is_digit(radix.max(10)) {
+            break;
+        }
+        number.push(c);
+        chars.next();
+    }
+    let digits = &number[2..];
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(format!("Invalid {} literal.", name));
+    }
+    number_token(number, true)
+}
+
+// Literals beyond f64's range are rejected here rather than quietly
+// evaluating to infinity later on.
+fn number_token(raw: String, is_integer: bool) -> Result<Token, String> {
+    match number_value(&raw) {
+        Some(value) if value.is_infinite() => Err("Number literal out of range.".to_string()),
+        Some(value) => Ok(Token::Number {
+            raw,
+            value,
+            is_integer,
+        }),
+        None => Err("Invalid number literal.".to_string()),
     }
-    Token::Number(number)
 }
 
-fn tokenize_string(chars: &mut PeekMoreIterator<Chars>) -> Token {
+fn number_value(lexeme: &str) -> Option<f64> {
+    let radix = match lexeme.get(..2) {
+        Some("0x") | Some("0X") => 16,
+        Some("0o") | Some("0O") => 8,
+        Some("0b") | Some("0B") => 2,
+        _ => return lexeme.parse().ok(),
+    };
+    lexeme[2..].chars().try_fold(0.0, |value, c| {
+        Some(value * radix as f64 + c.to_digit(radix)? as f64)
+    })
+}
+
+fn tokenize_string(chars: &mut Scanner) -> Result<Token, String> {
     let mut string = String::new();
-    let mut last_char = '"';
+    let mut terminated = false;
+    let mut error = None;
     while let Some(c) = chars.next() {
-        last_char = c;
-        if c == '"' {
-            break;
+        match c {
+            '"' => {
+                terminated = true;
+                break;
+            }
+            '\\' => match tokenize_escape(chars) {
+                Ok(escaped) => string.push(escaped),
+                Err(message) => {
+                    error.get_or_insert(message);
+                }
+            },
+            _ => string.push(c),
+        }
+    }
+    if !terminated {
+        return Err("Unterminated string.".to_string());
+    }
+    match error {
+        Some(message) => Err(message),
+        None => Ok(Token::String(string)),
+    }
+}
+
+// Backtick strings take everything up to the closing backtick literally,
+// backslashes and line breaks included.
+fn tokenize_raw_string(chars: &mut Scanner) -> Result<Token, String> {
+    let mut string = String::new();
+    for c in chars.by_ref() {
+        if c == '`' {
+            return Ok(Token::String(string));
         }
         string.push(c);
     }
-    if last_char != '"' {
-        return Token::Invalid(TokenizerError {
-            line: 1,
-            message: "Unterminated string.".to_string(),
-        });
+    Err("Unterminated raw string.".to_string())
+}
+
+// Unlike strings, char literals end at a line break.
+fn tokenize_char(chars: &mut Scanner) -> Result<Token, String> {
+    let mut contents = Vec::new();
+    let mut terminated = false;
+    let mut error = None;
+    while let Some(&c) = chars.peek() {
+        if c == '\n' || c == '\r' {
+            break;
+        }
+        chars.next();
+        match c {
+            '\'' => {
+                terminated = true;
+                break;
+            }
+            '\\' => match tokenize_escape(chars) {
+                Ok(escaped) => contents.push(escaped),
+                Err(message) => {
+                    error.get_or_insert(message);
+                }
+            },
+            _ => contents.push(c),
+        }
+    }
+    if !terminated {
+        return Err("Unterminated character literal.".to_string());
+    }
+    if let Some(message) = error {
+        return Err(message);
+    }
+    match contents.as_slice() {
+        [c] => Ok(Token::Char(*c)),
+        [] => Err("Empty character literal.".to_string()),
+        _ => Err("Character literal must contain exactly one character.".to_string()),
+    }
+}
+
+// Unknown escapes leave the offending char unconsumed so the caller still
+// sees a closing quote or newline that follows the backslash.
+fn tokenize_escape(chars: &mut Scanner) -> Result<char, String> {
+    let escaped = match chars.peek() {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('\\') => '\\',
+        Some('"') => '"',
+        Some('\'') => '\'',
+        Some('0') => '\0',
+        Some('u') => {
+            chars.next();
+            return tokenize_unicode_escape(chars);
+        }
+        Some(c) => return Err(format!("Invalid escape sequence: \\{}", c)),
+        None => return Err("Unterminated string.".to_string()),
+    };
+    chars.next();
+    Ok(escaped)
+}
+
+fn tokenize_unicode_escape(chars: &mut Scanner) -> Result<char, String> {
+    if chars.peek() != Some(&'{') {
+        return Err("Invalid unicode escape: expected '{' after \\u.".to_string());
+    }
+    chars.next();
+    let mut digits = String::new();
+    loop {
+        match chars.peek() {
+            Some('}') => {
+                chars.next();
+                break;
+            }
+            Some(&c) if c.is_ascii_hexdigit() => {
+                digits.push(c);
+                chars.next();
+            }
+            Some(&c) if c != '"' && c != '\n' => {
+                return Err(format!(
+                    "Invalid unicode escape: '{}' is not a hex digit.",
+                    c
+                ));
+            }
+            _ => return Err("Invalid unicode escape: missing closing '}'.".to_string()),
+        }
+    }
+    if digits.is_empty() {
+        return Err("Invalid unicode escape: empty braces.".to_string());
+    }
+    let code = u32::from_str_radix(&digits, 16).unwrap_or(u32::MAX);
+    if (0xD800..=0xDFFF).contains(&code) {
+        return Err(format!(
+            "Invalid unicode escape: {} is a surrogate code point.",
+            digits
+        ));
     }
-    Token::String(string)
+    char::from_u32(code)
+        .ok_or_else(|| format!("Invalid unicode escape: {} is out of range.", digits))
 }
 
-fn tokenize_identifier(first_char: char, chars: &mut PeekMoreIterator<Chars>) -> Token {
+// Returns the comment text between the outermost delimiters.
+fn tokenize_block_comment(chars: &mut Scanner) -> Result<String, String> {
+    let mut depth = 1;
+    let mut text = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                depth += 1;
+                text.push_str("/*");
+            }
+            '*' if chars.peek() == Some(&'/') => {
+                chars.next();
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(text);
+                }
+                text.push_str("*/");
+            }
+            _ => text.push(c),
+        }
+    }
+    Err("Unterminated block comment.".to_string())
+}
+
+fn tokenize_identifier(
+    first_char: char,
+    chars: &mut Scanner,
+    interner: Option<&mut HashSet<Rc<str>>>,
+) -> Token {
     let mut identifier = String::new();
     identifier.push(first_char);
     while let Some(&c) = chars.peek() {
@@ -358,10 +1232,49 @@ fn tokenize_identifier(first_char: char, chars: &mut PeekMoreIterator<Chars>) ->
             break;
         }
     }
-    for keyword in RESERVED_KEYWORDS.iter() {
-        if keyword.name == identifier {
-            return keyword.token.clone();
-        }
+    if let Some(keyword) = keyword_token(&identifier) {
+        return keyword;
     }
-    Token::Identifier(identifier)
+    let name = match interner {
+        Some(interner) => match interner.get(identifier.as_str()) {
+            Some(name) => Rc::clone(name),
+            None => {
+                let name: Rc<str> = identifier.into();
+                interner.insert(Rc::clone(&name));
+                name
+            }
+        },
+        None => identifier.into(),
+    };
+    Token::Identifier(name)
+}
+
+// Keywords are case-sensitive: `While` is an identifier.
+pub fn keyword_token(name: &str) -> Option<Token> {
+    let token = match name {
+        "and" => Token::And,
+        "class" => Token::Class,
+        "else" => Token::Else,
+        "false" => Token::False,
+        "for" => Token::For,
+        "fun" => Token::Fun,
+        "if" => Token::If,
+        "nil" => Token::Nil,
+        "or" => Token::Or,
+        "print" => Token::Print,
+        "return" => Token::Return,
+        "super" => Token::Super,
+        "this" => Token::This,
+        "true" => Token::True,
+        "var" => Token::Var,
+        "while" => Token::While,
+        "break" => Token::Break,
+        "continue" => Token::Continue,
+        _ => return None,
+    };
+    Some(token)
+}
+
+pub fn is_keyword(name: &str) -> bool {
+    keyword_token(name).is_some()
 }
@@ -0,0 +1,37 @@
+use super::scanner::source_lines;
+use super::{TokenizerError, TokenizerOptions};
+
+// Formats an error the way rustc does for a single line: the message, its
+// location, the offending source line and a caret under the bad column.
+pub fn render_error(source: &str, err: &TokenizerError) -> String {
+    render_error_with_options(source, err, &TokenizerOptions::default())
+}
+
+// The options must be the ones the error was reported with, since the tab
+// width decides which character a column refers to.
+pub fn render_error_with_options(
+    source: &str,
+    err: &TokenizerError,
+    options: &TokenizerOptions,
+) -> String {
+    let line = source_lines(source).nth(err.line - 1).unwrap_or("");
+    let gutter = " ".repeat(err.line.to_string().len());
+    let tab_width = options.tab_width.max(1);
+    // Keep tabs in the padding so the caret lines up however they render.
+    let mut column = 0;
+    let padding = line
+        .chars()
+        .take_while(|&c| {
+            column = match c {
+                '\t' => (column / tab_width + 1) * tab_width,
+                _ => column + 1,
+            };
+            column < err.column
+        })
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    format!(
+        "error: {}\n{}--> line {}, col {}\n{} |\n{} | {}\n{} | {}^",
+        err.message, gutter, err.line, err.column, gutter, err.line, line, gutter, padding
+    )
+}
@@ -0,0 +1,104 @@
+use peekmore::{PeekMore, PeekMoreIterator};
+use std::str::Chars;
+
+pub struct Scanner<'a> {
+    chars: PeekMoreIterator<Chars<'a>>,
+    line: usize,
+    column: usize,
+    offset: usize,
+    tab_width: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        // A leading byte-order mark is not source text; offsets still count it.
+        let mut start = match input.starts_with('\u{feff}') {
+            true => '\u{feff}'.len_utf8(),
+            false => 0,
+        };
+        // Neither is a `#!` line opening the file. Its line break is left in
+        // place so line numbers stay right.
+        if input[start..].starts_with("#!") {
+            start += input[start..]
+                .find(['\n', '\r'])
+                .unwrap_or(input.len() - start);
+        }
+        Self {
+            chars: input[start..].chars().peekmore(),
+            line: 1,
+            column: 0,
+            offset: start,
+            tab_width: 1,
+        }
+    }
+
+    pub fn with_tab_width(input: &'a str, tab_width: usize) -> Self {
+        Self {
+            tab_width: tab_width.max(1),
+            ..Self::new(input)
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    // Column of the last consumed char, 1-based. Zero right after a line break.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    // Byte offset just past the last consumed char.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+
+    pub fn peek_nth(&mut self, n: usize) -> Option<&char> {
+        self.chars.peek_nth(n)
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.offset += c.len_utf8();
+        // A `\r` only ends a line on its own; in a `\r\n` pair the `\n` is counted.
+        if c == '\n' || (c == '\r' && self.chars.peek() != Some(&'\n')) {
+            self.line += 1;
+            self.column = 0;
+        } else if c == '\t' {
+            // A tab runs to the next tab stop, like in an editor.
+            self.column = (self.column / self.tab_width + 1) * self.tab_width;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+}
+
+// Splits source into lines the way the scanner counts them: `\r\n`, `\n`
+// and a lone `\r` each end a line. Like `str::lines`, a final line break
+// doesn't start another line.
+pub fn source_lines(source: &str) -> impl Iterator<Item = &str> {
+    let mut rest = source;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let end = rest.find(['\n', '\r']).unwrap_or(rest.len());
+        let line = &rest[..end];
+        let line_break = match &rest[end..] {
+            tail if tail.starts_with("\r\n") => 2,
+            "" => 0,
+            _ => 1,
+        };
+        rest = &rest[end + line_break..];
+        Some(line)
+    })
+}
@@ -0,0 +1,46 @@
+use super::Token;
+
+// Rebuilds source text that tokenizes back to the same tokens. Layout is
+// not preserved: tokens are separated by single spaces, except that a line
+// comment has to end its line.
+pub fn tokens_to_source(tokens: &[Token]) -> String {
+    let mut source = String::new();
+    for token in tokens {
+        let text = match token {
+            Token::Invalid(_) | Token::WhiteSpace(_) | Token::EOF => continue,
+            Token::String(s) => quote(s, '"'),
+            Token::Char(c) => quote(&c.to_string(), '\''),
+            token => token.lexeme(),
+        };
+        if !source.is_empty() && !source.ends_with('\n') {
+            source.push(' ');
+        }
+        source.push_str(&text);
+        if matches!(token, Token::LineComment(_)) {
+            source.push('\n');
+        }
+    }
+    source
+}
+
+// The inverse of the tokenizer's escape handling.
+fn quote(text: &str, delimiter: char) -> String {
+    let mut quoted = String::from(delimiter);
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            c if c == delimiter => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push(delimiter);
+    quoted
+}
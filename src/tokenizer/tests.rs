@@ -0,0 +1,733 @@
+use super::*;
+
+// Just the tokens, EOF included.
+fn tokens(input: &str) -> Vec<Token> {
+    tokenize(input)
+        .into_iter()
+        .map(|spanned| spanned.token)
+        .collect()
+}
+
+// The value of a source that is a single number literal.
+fn number(input: &str) -> f64 {
+    match tokens(input)[..] {
+        [Token::Number { value, .. }, Token::EOF] => value,
+        ref other => panic!("expected one number, got {:?}", other),
+    }
+}
+
+fn errors(input: &str) -> Vec<TokenizerError> {
+    tokenize_checked(input).1
+}
+
+#[test]
+fn unterminated_string_reports_its_line() {
+    let errors = errors("1;\n2;\n3;\n4;\nprint \"oops");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, 5);
+    assert_eq!(errors[0].message, "Unterminated string.");
+}
+
+#[test]
+fn invalid_number_reports_its_line() {
+    let errors = errors("var a;\n\nvar b = 0x;");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, 3);
+}
+
+#[test]
+fn multi_line_string_advances_the_line() {
+    let tokens = tokenize("\"one\ntwo\nthree\" after");
+    assert_eq!(
+        tokens[0].token,
+        Token::String("one\ntwo\nthree".to_string())
+    );
+    assert_eq!(tokens[1].token, Token::Identifier("after".into()));
+    assert_eq!(tokens[1].line, 3);
+}
+
+#[test]
+fn single_line_block_comment_is_skipped() {
+    assert_eq!(
+        tokens("a /* note */ b"),
+        [
+            Token::Identifier("a".into()),
+            Token::Identifier("b".into()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn multi_line_block_comment_counts_lines() {
+    let tokens = tokenize("/* one\ntwo\n*/ a");
+    assert_eq!(tokens[0].token, Token::Identifier("a".into()));
+    assert_eq!(tokens[0].line, 3);
+}
+
+#[test]
+fn unterminated_block_comment_is_an_error() {
+    let errors = errors("a /* never closed");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Unterminated block comment.");
+}
+
+#[test]
+fn nested_block_comments_close_at_the_outer_end() {
+    assert_eq!(
+        tokens("/* a /* b /* c */ */ d */ x"),
+        [Token::Identifier("x".into()), Token::EOF]
+    );
+}
+
+#[test]
+fn unbalanced_inner_block_comment_is_unterminated() {
+    let errors = errors("/* a /* b */ x");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Unterminated block comment.");
+}
+
+#[test]
+fn supported_escapes_are_decoded() {
+    assert_eq!(
+        tokens(r#""\n\t\r\\\"\'\0""#)[0],
+        Token::String("\n\t\r\\\"'\0".to_string())
+    );
+}
+
+#[test]
+fn unknown_escape_is_an_error() {
+    let errors = errors(r#""a\qb""#);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Invalid escape sequence: \\q");
+}
+
+#[test]
+fn display_quotes_the_decoded_string() {
+    assert_eq!(tokens(r#""a\tb""#)[0].to_string(), "STRING \"a\tb\" a\tb");
+}
+
+#[test]
+fn unicode_escapes_decode_bmp_and_astral_chars() {
+    assert_eq!(tokens(r#""\u{e9}""#)[0], Token::String("é".to_string()));
+    assert_eq!(tokens(r#""\u{1F600}""#)[0], Token::String("😀".to_string()));
+}
+
+#[test]
+fn malformed_unicode_escapes_are_errors() {
+    let message = |input| errors(input)[0].message.clone();
+    assert_eq!(
+        message(r#""\u{}""#),
+        "Invalid unicode escape: empty braces."
+    );
+    assert_eq!(
+        message(r#""\u41""#),
+        "Invalid unicode escape: expected '{' after \\u."
+    );
+    assert_eq!(
+        message(r#""\u{D800}""#),
+        "Invalid unicode escape: D800 is a surrogate code point."
+    );
+    assert_eq!(
+        message(r#""\u{110000}""#),
+        "Invalid unicode escape: 110000 is out of range."
+    );
+}
+
+#[test]
+fn crlf_lf_and_bare_cr_each_end_one_line() {
+    let lines = tokenize("a\r\nb\nc\rd\r\n\re")
+        .iter()
+        .map(|spanned| spanned.line)
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [1, 2, 3, 4, 6, 6]);
+}
+
+#[test]
+fn unexpected_character_reports_its_column() {
+    let errors = errors("var a = @;");
+    assert_eq!((errors[0].line, errors[0].column), (1, 9));
+}
+
+#[test]
+fn unterminated_string_reports_the_opening_quote() {
+    let errors = errors("a;\nb = \"abc");
+    assert_eq!((errors[0].line, errors[0].column), (2, 5));
+}
+
+#[test]
+fn spans_count_bytes_of_multi_byte_identifiers() {
+    let spanned = tokenize_spanned("a café;");
+    assert_eq!(spanned[1].0, Token::Identifier("café".into()));
+    assert_eq!(spanned[1].1, Span { start: 2, end: 7 });
+    assert_eq!(spanned[2].1, Span { start: 7, end: 8 });
+}
+
+#[test]
+fn token_type_drops_the_payload() {
+    let types = tokens("1 \"s\" x while")
+        .iter()
+        .map(Token::token_type)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            TokenType::Number,
+            TokenType::String,
+            TokenType::Identifier,
+            TokenType::While,
+            TokenType::EOF
+        ]
+    );
+}
+
+#[test]
+fn lexeme_is_the_source_spelling() {
+    let lexemes = tokens("while >= name 1.50")
+        .iter()
+        .map(Token::lexeme)
+        .collect::<Vec<_>>();
+    assert_eq!(lexemes, ["while", ">=", "name", "1.50", ""]);
+}
+
+#[test]
+fn hex_literals() {
+    assert_eq!(number("0xFF"), 255.0);
+    assert_eq!(number("0x0"), 0.0);
+    assert_eq!(errors("0x")[0].message, "Invalid hex literal.");
+}
+
+#[test]
+fn binary_and_octal_literals() {
+    assert_eq!(number("0b1010"), 10.0);
+    assert_eq!(number("0o17"), 15.0);
+    assert_eq!(errors("0b2")[0].message, "Invalid binary literal.");
+    assert_eq!(errors("0o8")[0].message, "Invalid octal literal.");
+}
+
+#[test]
+fn scientific_notation() {
+    assert_eq!(number("1e10"), 1e10);
+    assert_eq!(number("2.5e-3"), 2.5e-3);
+    // Without exponent digits the `e` starts an identifier.
+    assert_eq!(tokens("1e")[1], Token::Identifier("e".into()));
+}
+
+#[test]
+fn digit_separators() {
+    assert_eq!(number("1_000_000"), 1_000_000.0);
+    assert_eq!(number("12.345_6"), 12.3456);
+    assert_eq!(errors("1__0")[0].message, "Invalid number literal.");
+    assert_eq!(errors("1_.5")[0].message, "Invalid number literal.");
+}
+
+#[test]
+fn unicode_identifiers() {
+    assert_eq!(tokens("café")[0], Token::Identifier("café".into()));
+    assert_eq!(tokens("λx")[0], Token::Identifier("λx".into()));
+}
+
+#[test]
+fn every_keyword_is_recognized() {
+    let source = "and class else false for fun if nil or print return super this true var while \
+                  break continue";
+    for (word, token) in source.split_whitespace().zip(tokens(source)) {
+        assert_eq!(token.lexeme(), word);
+        assert_ne!(token.token_type(), TokenType::Identifier, "{}", word);
+    }
+    assert_eq!(tokens("While")[0], Token::Identifier("While".into()));
+}
+
+#[test]
+fn checked_tokenize_separates_errors() {
+    let (tokens, errors) = tokenize_checked("@ ok #");
+    let tokens = tokens.into_iter().map(|t| t.token).collect::<Vec<_>>();
+    assert_eq!(tokens, [Token::Identifier("ok".into()), Token::EOF]);
+    let errors = errors
+        .iter()
+        .map(|e| (e.column, e.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            (1, "Unexpected character: @"),
+            (6, "Unexpected character: #")
+        ]
+    );
+}
+
+#[test]
+fn iterator_matches_tokenize() {
+    let source = "// intro\nvar s = \"a /* not a comment */\"; /* block */ print s;";
+    assert_eq!(Tokenizer::new(source).collect::<Vec<_>>(), tokenize(source));
+}
+
+#[test]
+fn tokens_as_json() {
+    let expected = r#"[
+  {"type": "IDENTIFIER", "lexeme": "s", "literal": null, "line": 1},
+  {"type": "EQUAL", "lexeme": "=", "literal": null, "line": 1},
+  {"type": "STRING", "lexeme": "\"hi\"", "literal": "hi", "line": 2},
+  {"type": "NUMBER", "lexeme": "1", "literal": 1.0, "line": 2},
+  {"type": "EOF", "lexeme": "", "literal": null, "line": 2}
+]"#;
+    assert_eq!(to_json(&tokenize("s =\n\"hi\" 1 @")), expected);
+}
+
+#[test]
+fn percent_operator() {
+    assert_eq!(tokens("7%3")[1], Token::Percent);
+}
+
+#[test]
+fn ternary_punctuation() {
+    assert_eq!(
+        tokens("a ? b : c"),
+        [
+            Token::Identifier("a".into()),
+            Token::Question,
+            Token::Identifier("b".into()),
+            Token::Colon,
+            Token::Identifier("c".into()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn symbolic_logical_operators() {
+    assert_eq!(tokens("a && b")[1], Token::AmpAmp);
+    assert_eq!(tokens("a || b")[1], Token::PipePipe);
+    assert_eq!(errors("a & b")[0].message, "Unexpected character: &");
+}
+
+#[test]
+fn compound_assignment_operators() {
+    assert_eq!(
+        tokens("+= -= *= /="),
+        [
+            Token::PlusEqual,
+            Token::MinusEqual,
+            Token::StarEqual,
+            Token::SlashEqual,
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn character_literals() {
+    assert_eq!(tokens("'a'")[0], Token::Char('a'));
+    assert_eq!(tokens(r"'\n'")[0], Token::Char('\n'));
+    assert_eq!(errors("''")[0].message, "Empty character literal.");
+    assert_eq!(errors("'a")[0].message, "Unterminated character literal.");
+}
+
+#[test]
+fn line_comment_text_is_kept_verbatim() {
+    let tokens = tokenize_with_trivia("a // keep  this \nb");
+    assert_eq!(
+        tokens[1].token,
+        Token::LineComment(" keep  this ".to_string())
+    );
+    assert_eq!(tokens[2].token, Token::Identifier("b".into()));
+}
+
+#[test]
+fn rendered_error_puts_the_caret_under_the_bad_character() {
+    let source = "var a = 1;\nprint a @ 2;";
+    let error = &errors(source)[0];
+    assert_eq!(
+        render_error(source, error),
+        "error: Unexpected character: @\n \
+         --> line 2, col 9\n  \
+         |\n\
+         2 | print a @ 2;\n  \
+         |         ^"
+    );
+}
+
+#[test]
+fn rendered_error_splits_lines_like_the_scanner() {
+    let source = "print 1;\rprint @;";
+    let rendered = render_error(source, &errors(source)[0]);
+    assert!(
+        rendered.ends_with("2 | print @;\n  |       ^"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn source_lines_match_scanner_line_breaks() {
+    let lines = source_lines("a\r\nb\rc\nd\n").collect::<Vec<_>>();
+    assert_eq!(lines, ["a", "b", "c", "d"]);
+    assert_eq!(source_lines("\r\r").collect::<Vec<_>>(), ["", ""]);
+}
+
+#[test]
+fn summary_counts_lines_like_the_scanner() {
+    let source = "a\rb\r\nc\n";
+    let tokens = tokenize(source);
+    let summary = TokenSummary {
+        tokens: &tokens,
+        source,
+    };
+    assert_eq!(summary.to_string(), "IDENTIFIER: 3\nLines: 3");
+}
+
+#[test]
+fn leading_byte_order_mark_is_skipped() {
+    let tokens = tokenize("\u{feff}var a;");
+    assert_eq!(tokens[0].token, Token::Var);
+    assert_eq!(tokens[0].span, Span { start: 3, end: 6 });
+}
+
+#[test]
+fn embedded_byte_order_mark_is_an_error() {
+    let errors = errors("var \u{feff}a;");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Unexpected character: \u{feff}");
+}
+
+#[test]
+fn coalesced_errors_merge_a_run() {
+    let messages = |tokens: Vec<Spanned<Token>>| {
+        tokens
+            .into_iter()
+            .filter_map(|spanned| match spanned.token {
+                Token::Invalid(e) => Some(e.message),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        messages(tokenize("@#$")),
+        [
+            "Unexpected character: @",
+            "Unexpected character: #",
+            "Unexpected character: $"
+        ]
+    );
+    assert_eq!(
+        messages(tokenize_coalesced("@#$")),
+        ["Unexpected characters: @#$"]
+    );
+}
+
+#[test]
+fn tokenize_reader_reads_the_whole_source() {
+    let source = "print \"from a reader\";";
+    let tokens = tokenize_reader(io::Cursor::new(source)).unwrap();
+    assert_eq!(tokens, tokenize(source));
+}
+
+#[test]
+fn tokenize_reader_rejects_invalid_utf8() {
+    let error = tokenize_reader(io::Cursor::new([0xff, 0xfe])).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn presized_output_matches_the_iterator() {
+    for source in [
+        "",
+        "x",
+        "var s = \"text\"; // note",
+        &"a + 1;\n".repeat(500),
+    ] {
+        assert_eq!(tokenize(source), Tokenizer::new(source).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn interned_identifiers_share_one_allocation() {
+    let tokens = tokenize_interned("foo bar foo");
+    match (&tokens[0].token, &tokens[2].token) {
+        (Token::Identifier(first), Token::Identifier(second)) => {
+            assert!(Rc::ptr_eq(first, second))
+        }
+        other => panic!("expected identifiers, got {:?}", other),
+    }
+}
+
+#[test]
+fn trailing_dot_is_not_part_of_the_number() {
+    assert_eq!(tokens("123.")[1], Token::Dot);
+    assert_eq!(tokens("123.")[0].lexeme(), "123");
+    assert_eq!(number("123.456"), 123.456);
+}
+
+#[test]
+fn decimal_point_needs_a_digit_after_it() {
+    let types = |source| {
+        tokens(source)
+            .iter()
+            .map(Token::token_type)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(number("1.5"), 1.5);
+    assert_eq!(
+        types("1.a"),
+        [
+            TokenType::Number,
+            TokenType::Dot,
+            TokenType::Identifier,
+            TokenType::EOF
+        ]
+    );
+    assert_eq!(
+        types("1..2"),
+        [
+            TokenType::Number,
+            TokenType::Dot,
+            TokenType::Dot,
+            TokenType::Number,
+            TokenType::EOF
+        ]
+    );
+}
+
+#[test]
+fn numbers_beyond_f64_are_rejected() {
+    assert_eq!(errors("1e400")[0].message, "Number literal out of range.");
+    let huge = format!("1{}", "0".repeat(400));
+    assert_eq!(errors(&huge)[0].message, "Number literal out of range.");
+    let long = format!("1{}", "0".repeat(30));
+    assert_eq!(number(&long), 1e30);
+}
+
+#[test]
+fn control_characters_are_errors() {
+    assert_eq!(
+        errors("a\0b")[0].message,
+        "Unexpected control character (0x00)."
+    );
+    assert_eq!(
+        errors("a\x07b")[0].message,
+        "Unexpected control character (0x07)."
+    );
+}
+
+#[test]
+fn token_list_matches_printing_each_token() {
+    let tokens = tokenize("var s = \"x\" @ 1.5;");
+    let manual = tokens
+        .iter()
+        .filter(|spanned| !matches!(spanned.token, Token::Invalid(_)))
+        .map(|spanned| spanned.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(TokenList(&tokens).to_string(), manual);
+}
+
+#[test]
+fn raw_strings_keep_backslashes() {
+    assert_eq!(
+        tokens(r"`C:\new\table`")[0],
+        Token::String(r"C:\new\table".to_string())
+    );
+    assert_eq!(errors("`open")[0].message, "Unterminated raw string.");
+}
+
+#[test]
+fn brackets_are_their_own_tokens() {
+    let types: Vec<TokenType> = tokens("a[0]").iter().map(Token::token_type).collect();
+    assert_eq!(
+        types,
+        [
+            TokenType::Identifier,
+            TokenType::LeftBracket,
+            TokenType::Number,
+            TokenType::RightBracket,
+            TokenType::EOF,
+        ]
+    );
+}
+
+#[test]
+fn summary_counts_tokens_by_type() {
+    let source = "var a = 1;\nvar b = a + 2;\n";
+    let tokens = tokenize(source);
+    let summary = TokenSummary {
+        tokens: &tokens,
+        source,
+    };
+    assert_eq!(
+        summary.to_string(),
+        "IDENTIFIER: 3, VAR: 2, EQUAL: 2, NUMBER: 2, SEMICOLON: 2, PLUS: 1\nLines: 2"
+    );
+}
+
+#[test]
+fn rebuilt_source_tokenizes_to_the_same_types() {
+    let programs = [
+        "var greeting = \"hi\\tthere\\n\"; print greeting;",
+        "fun add(a, b) { return a + b; } // sum\nprint add(1_000, 0x1F);",
+        "class A < B { init() { this.x = 'c'; } } /* block */ a[0] >= -1.5;",
+        "while (!done and n != 2) { n = n - 1; }",
+    ];
+    for program in programs {
+        let types = |source: &str| {
+            tokens(source)
+                .iter()
+                .map(Token::token_type)
+                .collect::<Vec<_>>()
+        };
+        let rebuilt = tokens_to_source(&tokens(program));
+        assert_eq!(types(&rebuilt), types(program), "{}", rebuilt);
+    }
+}
+
+#[test]
+fn strict_tokenizing_stops_at_the_first_error() {
+    let error = tokenize_strict("var a = 1;\nvar b = @;\nvar c = \"open").unwrap_err();
+    assert_eq!(error.line, 2);
+    assert_eq!(error.message, "Unexpected character: @");
+    assert_eq!(tokenize_strict("1 + 2").unwrap().len(), 4);
+}
+
+#[test]
+fn shebang_line_is_skipped_up_to_any_line_break() {
+    for source in [
+        "#!/usr/bin/env lox\nprint 1;",
+        "#!/usr/bin/env lox\r\nprint 1;",
+        "#!/usr/bin/env lox\rprint 1;",
+    ] {
+        let tokens = tokenize(source);
+        assert_eq!(tokens[0].token, Token::Print, "{:?}", source);
+        assert_eq!(tokens[0].line, 2);
+    }
+    assert!(tokens("#!").iter().eq([&Token::EOF]));
+}
+
+#[test]
+fn hash_is_only_skipped_at_the_start() {
+    let errors = errors("print 1;\n#!/usr/bin/env lox");
+    assert_eq!(errors[0].line, 2);
+    assert_eq!(errors[0].message, "Unexpected character: #");
+}
+
+#[test]
+fn tabs_advance_to_the_next_tab_stop() {
+    let columns = |source: &str, tab_width: usize| {
+        let options = TokenizerOptions::default().tab_width(tab_width);
+        tokenize_with_options(source, &options)
+            .into_iter()
+            .filter_map(|spanned| match spanned.token {
+                Token::Invalid(error) => Some(error.column),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(columns("\t\t@\na\t@", 1), [3, 3]);
+    assert_eq!(columns("\t\t@\na\t@", 4), [9, 5]);
+}
+
+#[test]
+fn options_combine() {
+    let options = TokenizerOptions::default()
+        .keep_comments(true)
+        .coalesce_errors(true);
+    let tokens = tokenize_with_options("@#$ // note", &options)
+        .into_iter()
+        .map(|spanned| spanned.token)
+        .collect::<Vec<_>>();
+    assert_eq!(tokens.len(), 3);
+    assert!(matches!(&tokens[0], Token::Invalid(e) if e.message == "Unexpected characters: @#$"));
+    assert_eq!(tokens[1], Token::LineComment(" note".to_string()));
+    assert_eq!(tokens[2], Token::EOF);
+}
+
+#[test]
+fn numbers_display_their_precomputed_value() {
+    let displayed = tokens("0x1F 1_000 2.5_0")
+        .iter()
+        .map(Token::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        displayed,
+        [
+            "NUMBER 0x1F 31.0",
+            "NUMBER 1000 1000.0",
+            "NUMBER 2.50 2.5",
+            "EOF  null"
+        ]
+    );
+}
+
+#[test]
+fn verbatim_spans_rebuild_the_source_exactly() {
+    let sources = [
+        "fun f() {\n\tvar s = \"a\\tb\"; // tab\n  return 1_000 @ /* x */ s;\r\n}\n",
+        "#!/usr/bin/env lox\nprint 1;",
+        "\u{feff}#!/usr/bin/env lox\r  print 1;",
+        "\u{feff}print 1;",
+    ];
+    for source in sources {
+        let mut rebuilt = String::new();
+        for spanned in tokenize_verbatim(source) {
+            assert_eq!(spanned.span.start, rebuilt.len(), "{:?}", source);
+            let text = &source[spanned.span.start..spanned.span.end];
+            // Whitespace carries exactly the text it spans.
+            if let Token::WhiteSpace(whitespace) = &spanned.token {
+                assert_eq!(whitespace, text);
+            }
+            rebuilt.push_str(text);
+        }
+        assert_eq!(rebuilt, source);
+    }
+}
+
+#[test]
+fn verbatim_whitespace_carries_its_text() {
+    let whitespace = tokenize_verbatim("#!lox\n\t a \r\n b")
+        .into_iter()
+        .filter_map(|spanned| match spanned.token {
+            Token::WhiteSpace(text) => Some(text),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(whitespace, ["#!lox", "\n\t ", " \r\n "]);
+}
+
+#[test]
+fn question_dot_is_one_token_but_question_stays_alone() {
+    let types = |source: &str| {
+        tokens(source)
+            .iter()
+            .map(Token::token_type)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        types("a?.b"),
+        [
+            TokenType::Identifier,
+            TokenType::QuestionDot,
+            TokenType::Identifier,
+            TokenType::EOF
+        ]
+    );
+    assert_eq!(
+        types("a?b:c"),
+        [
+            TokenType::Identifier,
+            TokenType::Question,
+            TokenType::Identifier,
+            TokenType::Colon,
+            TokenType::Identifier,
+            TokenType::EOF
+        ]
+    );
+}
+
+#[test]
+fn only_numbers_without_a_fraction_or_exponent_are_integers() {
+    let is_integer = |source: &str| tokens(source)[0].is_integer();
+    assert!(is_integer("3"));
+    assert!(is_integer("0x1F"));
+    assert!(is_integer("1_000"));
+    assert!(!is_integer("3.0"));
+    assert!(!is_integer("1e3"));
+    assert!(!is_integer("\"3\""));
+}
@@ -0,0 +1,128 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn lox_command() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+}
+
+// Runs the binary as `<command> <file> [options]` with `source` written to
+// a fresh scratch file.
+fn lox(command: &str, source: &str, options: &[&str]) -> Output {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let path = env::temp_dir().join(format!(
+        "lox-cli-{}-{}.lox",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&path, source).unwrap();
+    let output = lox_command()
+        .arg(command)
+        .arg(&path)
+        .args(options)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).ok();
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn runtime_error_exits_70() {
+    let output = lox("evaluate", "-\"a\"", &[]);
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(stderr(&output), "Operand must be a number.\n[line 1]\n");
+}
+
+#[test]
+fn run_prints_to_stdout() {
+    let output = lox("run", "print \"hello\";\nprint 1 + 2;\n", &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "hello\n3\n");
+}
+
+#[test]
+fn repl_keeps_state_between_lines() {
+    let mut child = lox_command()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"var a = 1\na + 1\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "2\n");
+}
+
+#[test]
+fn missing_file_exits_66() {
+    let path = env::temp_dir().join("lox-cli-this-file-does-not-exist.lox");
+    let output = lox_command().arg("run").arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(66));
+    assert_eq!(
+        stderr(&output),
+        format!("File not found: {}\n", path.display())
+    );
+}
+
+#[test]
+fn missing_filename_exits_64() {
+    let output = lox_command().arg("tokenize").output().unwrap();
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr(&output).starts_with("Usage: "));
+}
+
+#[test]
+fn unknown_command_exits_64() {
+    let output = lox("frobnicate", "", &[]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr(&output).starts_with("Unknown command: frobnicate\nUsage: "));
+}
+
+#[test]
+fn deep_nesting_exits_65() {
+    let source = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+    let output = lox("parse", &source, &[]);
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        stderr(&output),
+        "[line 1] Error at '(': Too many nested expressions.\n"
+    );
+}
+
+#[test]
+fn print_last_prints_the_final_expression() {
+    let output = lox("run", "1 + 1;", &["--print-last"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "2\n");
+    let output = lox("run", "1 + 1;\nvar a = 3;", &["--print-last"]);
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn max_errors_caps_the_lexical_errors_shown() {
+    let output = lox("tokenize", "@\n#\n$\n~\n`", &["--max-errors", "2"]);
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        stderr(&output),
+        "[line 1, col 1] Error: Unexpected character: @\n\
+         [line 2, col 1] Error: Unexpected character: #\n\
+         ... and 3 more errors.\n"
+    );
+    assert_eq!(stdout(&output), "EOF  null\n");
+}
@@ -0,0 +1,38 @@
+use interpreter_starter_rust::tokenizer::{is_keyword, keyword_token};
+use interpreter_starter_rust::{tokenize, Token};
+
+#[test]
+fn tokenize_is_usable_from_outside_the_crate() {
+    let tokens = tokenize("var answer = 42;")
+        .into_iter()
+        .map(|spanned| spanned.token.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        [
+            "VAR var null",
+            "IDENTIFIER answer null",
+            "EQUAL = null",
+            "NUMBER 42 42.0",
+            "SEMICOLON ; null",
+            "EOF  null"
+        ]
+    );
+}
+
+#[test]
+fn tokenize_reports_errors_as_invalid_tokens() {
+    let tokens = tokenize("@");
+    assert!(
+        matches!(&tokens[0].token, Token::Invalid(e) if e.message == "Unexpected character: @")
+    );
+}
+
+#[test]
+fn keywords_can_be_looked_up_from_outside_the_crate() {
+    assert_eq!(keyword_token("while"), Some(Token::While));
+    assert_eq!(keyword_token("foo"), None);
+    assert_eq!(keyword_token("While"), None);
+    assert!(is_keyword("class"));
+    assert!(!is_keyword("static"));
+}